@@ -0,0 +1,141 @@
+//! Compares the basic operations of [`CoWec`] against [`Vec`].
+//!
+//! Run with `cargo bench`. Besides the wall time, each benchmark reports how many allocations and
+//! reallocations one iteration does, counted by a wrapper around the system allocator.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use dasture::{CoWec, RCell};
+
+type C = CoWec<RCell, u32, ()>;
+
+/// The most a default (`u16`) block can hold.
+const FULL: usize = 4095;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static REALLOCS: AtomicUsize = AtomicUsize::new(0);
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        REALLOCS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Runs `f` repeatedly for about the `budget` and prints the per-iteration averages.
+fn bench<O, F: FnMut() -> O>(name: &str, mut f: F) {
+    let budget = Duration::from_millis(200);
+    // Warm up and find out how many iterations fit into the budget.
+    let start = Instant::now();
+    let mut iters = 0u32;
+    while start.elapsed() < budget / 4 {
+        black_box(f());
+        iters += 1;
+    }
+    let iters = iters.max(1) * 4;
+
+    let allocs = ALLOCS.load(Ordering::Relaxed);
+    let reallocs = REALLOCS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iters {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    let allocs = ALLOCS.load(Ordering::Relaxed) - allocs;
+    let reallocs = REALLOCS.load(Ordering::Relaxed) - reallocs;
+    let per_iter = |n: usize| n as f64 / f64::from(iters);
+    println!(
+        "{:<24} {:>12.1?} {:>8.1} allocs {:>8.1} reallocs",
+        name,
+        elapsed / iters,
+        per_iter(allocs),
+        per_iter(reallocs),
+    );
+}
+
+fn full_cowec() -> C {
+    C::from((0..FULL as u32).collect::<Vec<_>>())
+}
+
+fn full_vec() -> Vec<u32> {
+    (0..FULL as u32).collect()
+}
+
+/// Cheap pseudo-random indices, so the reads are not sequential.
+fn indices() -> impl Iterator<Item = usize> {
+    let mut state = 0x2545_f491u32;
+    (0..FULL).map(move |_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % FULL
+    })
+}
+
+fn main() {
+    bench("push/cowec", || {
+        let mut c = C::new_left();
+        for i in 0..FULL as u32 {
+            c.push_left(i).unwrap();
+        }
+        c
+    });
+    bench("push/vec", || {
+        let mut v = Vec::new();
+        for i in 0..FULL as u32 {
+            v.push(i);
+        }
+        v
+    });
+
+    let c = full_cowec();
+    let v = full_vec();
+    bench("random_read/cowec", || {
+        indices().map(|i| *c.get_left(i).unwrap()).sum::<u32>()
+    });
+    bench("random_read/vec", || indices().map(|i| v[i]).sum::<u32>());
+
+    bench("clone_read/cowec", || {
+        let shared = black_box(&c).clone();
+        *shared.get_left(FULL / 2).unwrap()
+    });
+    bench("clone_read/vec", || {
+        let shared = black_box(&v).clone();
+        shared[FULL / 2]
+    });
+
+    bench("clone_write/cowec", || {
+        let mut shared = black_box(&c).clone();
+        *shared.get_left_mut(FULL / 2).unwrap() += 1;
+        shared
+    });
+    bench("clone_write/vec", || {
+        let mut shared = black_box(&v).clone();
+        shared[FULL / 2] += 1;
+        shared
+    });
+
+    let c = C::from((0..1000).collect::<Vec<_>>());
+    let v = (0..1000).collect::<Vec<u32>>();
+    bench("iterate/cowec", || {
+        c.chunks_left(1000).flatten().sum::<u32>()
+    });
+    bench("iterate/vec", || v.iter().sum::<u32>());
+}
@@ -0,0 +1,45 @@
+//! Applies a sequence of operations decoded from the input to a few `CoWec`s sharing blocks and
+//! checks them against plain `Vec`s after each step.
+//!
+//! The checking itself is shared with the randomized tests of the crate. Run with
+//! `cargo fuzz run cowec_ops`.
+
+#![no_main]
+
+use dasture::model::{Model, Op};
+use libfuzzer_sys::fuzz_target;
+
+/// Decodes the operations, each one is a tag byte, a handle index and the arguments.
+///
+/// Running out of the input in the middle of an operation just ends the sequence.
+fn decode(data: &[u8]) -> Vec<(usize, Op)> {
+    let mut bytes = data.iter().copied();
+    let mut ops = Vec::new();
+    while let (Some(tag), Some(handle)) = (bytes.next(), bytes.next()) {
+        let op = match tag % 5 {
+            0 => match (bytes.next(), bytes.next()) {
+                (Some(pos), Some(val)) => Op::InsertAt(pos.into(), val.into()),
+                _ => break,
+            },
+            1 => match bytes.next() {
+                Some(pos) => Op::RemoveAt(pos.into()),
+                None => break,
+            },
+            2 => Op::Clone,
+            3 => Op::DropClone,
+            _ => match bytes.next() {
+                Some(pos) => Op::GetAt(pos.into()),
+                None => break,
+            },
+        };
+        ops.push((handle.into(), op));
+    }
+    ops
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut model = Model::new();
+    for (handle, op) in decode(data) {
+        model.apply(handle, op);
+    }
+});
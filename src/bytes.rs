@@ -0,0 +1,284 @@
+//! Methods specific to byte buffers (`T = u8`).
+
+use alloc::string::String;
+
+use super::{
+    Base64Error, CapacityError, CoWec, CoWecBlock, GrowthPolicy, HexError, LenCap, RefCnt,
+};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// The standard Base64 alphabet (RFC 4648).
+const BASE64_STD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The URL and file name safe Base64 alphabet (RFC 4648).
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+impl<R, U, L, G> CoWec<R, u8, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// XORs the left bytes with the `key`, repeating it as needed.
+    ///
+    /// Applying the same key again restores the original. The block is copied first if it is
+    /// shared, so other `CoWec`s sharing it are not affected. An empty key or other variants are
+    /// left unchanged.
+    ///
+    /// **This is not a cryptographically secure cipher**, it is only good for obfuscation.
+    pub fn encrypt_left_xor(&mut self, key: &[u8]) {
+        if let Some(bytes) = self.left_slice_mut() {
+            for (byte, k) in bytes.iter_mut().zip(key.iter().cycle()) {
+                *byte ^= k;
+            }
+        }
+    }
+
+    /// Formats the left bytes as a lowercase hexadecimal string.
+    ///
+    /// The other variants produce an empty string. The result is a plain [`String`], so it is not
+    /// limited by the size of a block even though it is twice as long as the data.
+    pub fn to_hex_left(&self) -> String {
+        let bytes = self.left_slice().unwrap_or_default();
+        let mut hex = String::with_capacity(2 * bytes.len());
+        for byte in bytes {
+            hex.push(char::from(HEX_DIGITS[usize::from(byte >> 4)]));
+            hex.push(char::from(HEX_DIGITS[usize::from(byte & 0xf)]));
+        }
+        hex
+    }
+
+    /// Encodes the left bytes as Base64, with the standard alphabet and padding.
+    ///
+    /// The other variants produce an empty string. Like with [`to_hex_left`][CoWec::to_hex_left],
+    /// the result may be longer than what fits into a block.
+    pub fn to_base64_left(&self) -> String {
+        let bytes = self.left_slice().unwrap_or_default();
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let mut group = [0; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let bits = u32::from(group[0]) << 16 | u32::from(group[1]) << 8 | u32::from(group[2]);
+            // 3 bytes make 4 sextets, a partial chunk of n bytes makes n + 1 of them.
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let sextet = (bits >> (18 - 6 * i)) & 0x3f;
+                    out.push(char::from(BASE64_STD[sextet as usize]));
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    /// Decodes a Base64 string in the standard alphabet into a left `CoWec`.
+    ///
+    /// The padding is optional, but if present, it must be correct.
+    ///
+    /// # Errors
+    ///
+    /// If the string contains characters outside of the alphabet, the padding is wrong or if the
+    /// decoded bytes don't fit into a block.
+    pub fn from_base64_left(s: &str) -> Result<Self, Base64Error> {
+        Self::decode_base64(s, BASE64_STD)
+    }
+
+    /// Decodes a Base64 string in the URL-safe alphabet into a left `CoWec`.
+    ///
+    /// Otherwise the same as [`from_base64_left`][CoWec::from_base64_left].
+    pub fn from_base64_url_left(s: &str) -> Result<Self, Base64Error> {
+        Self::decode_base64(s, BASE64_URL)
+    }
+
+    fn decode_base64(s: &str, alphabet: &[u8; 64]) -> Result<Self, Base64Error> {
+        let data = s.trim_end_matches('=');
+        let padding = s.len() - data.len();
+        if padding > 2 || (padding > 0 && s.len() & 3 != 0) || data.len() & 3 == 1 {
+            return Err(Base64Error::InvalidPadding);
+        }
+        let sextet = |c: char| {
+            alphabet
+                .iter()
+                .position(|&a| char::from(a) == c)
+                .ok_or(if c == '=' {
+                    Base64Error::InvalidPadding
+                } else {
+                    Base64Error::InvalidChar(c)
+                })
+        };
+        // Validate before allocating, the decoding can't fail afterwards.
+        for c in data.chars() {
+            sextet(c)?;
+        }
+        let len = data.len() * 3 / 4;
+        let limit = CoWecBlock::<R, u8, L>::LEN_MASK;
+        if len > limit {
+            return Err(Base64Error::CapacityOverflow(CapacityError {
+                requested: len,
+                limit,
+            }));
+        }
+        let block = unsafe { CoWecBlock::<R, u8, L>::create_for(len) };
+        let me = Self::from_left_block(block);
+        let mut pos = 0;
+        for chunk in data.as_bytes().chunks(4) {
+            let mut bits = 0;
+            for (i, &c) in chunk.iter().enumerate() {
+                let value = sextet(char::from(c)).expect("Validated above") as u32;
+                bits |= value << (18 - 6 * i);
+            }
+            // n sextets carry n - 1 whole bytes.
+            for i in 0..chunk.len() - 1 {
+                unsafe { CoWecBlock::insert(block, pos, (bits >> (16 - 8 * i)) as u8) };
+                pos += 1;
+            }
+        }
+        Ok(me)
+    }
+
+    /// Parses a hexadecimal string (of either case) into a left `CoWec`.
+    ///
+    /// # Errors
+    ///
+    /// If the string has an odd length, contains something else than hex digits or if the
+    /// decoded bytes don't fit into a block.
+    pub fn from_hex_left(hex: &str) -> Result<Self, HexError> {
+        if hex.len() & 1 != 0 {
+            return Err(HexError::OddLength);
+        }
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(HexError::InvalidChar(c));
+        }
+        let limit = CoWecBlock::<R, u8, L>::LEN_MASK;
+        if hex.len() / 2 > limit {
+            return Err(HexError::CapacityOverflow(CapacityError {
+                requested: hex.len() / 2,
+                limit,
+            }));
+        }
+        let digit = |c: u8| char::from(c).to_digit(16).expect("Validated above") as u8;
+        let block = unsafe { CoWecBlock::<R, u8, L>::create_for(hex.len() / 2) };
+        let me = Self::from_left_block(block);
+        for (i, pair) in hex.as_bytes().chunks_exact(2).enumerate() {
+            let byte = (digit(pair[0]) << 4) | digit(pair[1]);
+            unsafe { CoWecBlock::insert(block, i, byte) };
+        }
+        Ok(me)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, u8, ()>;
+
+    #[test]
+    fn xor() {
+        let mut c = C::from(&b"Hello"[..]);
+        let shared = c.clone();
+        c.encrypt_left_xor(&[0x01, 0x02]);
+        assert_eq!(c.left_slice(), Some(&b"Igmnn"[..]));
+        assert_eq!(shared.left_slice(), Some(&b"Hello"[..]));
+        c.encrypt_left_xor(&[0x01, 0x02]);
+        assert!(c == shared);
+        c.encrypt_left_xor(&[]);
+        assert!(c == shared);
+    }
+
+    #[test]
+    fn to_hex() {
+        let c = C::from(&[0x00, 0x0f, 0xa5, 0xff][..]);
+        assert_eq!(c.to_hex_left(), "000fa5ff");
+        assert_eq!(C::new_left().to_hex_left(), "");
+        assert_eq!(C::new_right().to_hex_left(), "");
+
+        let full = C::from(&[0xab; 4095][..]);
+        assert_eq!(full.to_hex_left(), "ab".repeat(4095));
+    }
+
+    #[test]
+    fn to_base64() {
+        let enc = |s: &str| C::from(s.as_bytes()).to_base64_left();
+        assert_eq!(enc(""), "");
+        assert_eq!(enc("f"), "Zg==");
+        assert_eq!(enc("fo"), "Zm8=");
+        assert_eq!(enc("foo"), "Zm9v");
+        assert_eq!(enc("foob"), "Zm9vYg==");
+        assert_eq!(enc("fooba"), "Zm9vYmE=");
+        assert_eq!(enc("foobar"), "Zm9vYmFy");
+        assert_eq!(C::from(&[0xfb, 0xff][..]).to_base64_left(), "+/8=");
+        assert_eq!(C::new_right().to_base64_left(), "");
+
+        let full = C::from(&[0; 4095][..]);
+        assert_eq!(full.to_base64_left(), "AAAA".repeat(1365));
+        let almost = C::from(&[0; 4094][..]);
+        assert_eq!(almost.to_base64_left(), "AAAA".repeat(1364) + "AAA=");
+    }
+
+    #[test]
+    fn from_base64() {
+        let dec = |s: &str| C::from_base64_left(s).map(|c| c.left_slice().unwrap().to_vec());
+        for plain in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let c = C::from(plain.as_bytes());
+            let encoded = c.to_base64_left();
+            assert_eq!(dec(&encoded).unwrap(), plain.as_bytes());
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(dec(unpadded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(dec("+/8=").unwrap(), [0xfb, 0xff]);
+        let url = C::from_base64_url_left("-_8").unwrap();
+        assert_eq!(url.left_slice(), Some(&[0xfb, 0xff][..]));
+
+        assert_eq!(dec("-_8="), Err(Base64Error::InvalidChar('-')));
+        assert_eq!(dec("Zm9!"), Err(Base64Error::InvalidChar('!')));
+        assert_eq!(dec("Zg="), Err(Base64Error::InvalidPadding));
+        assert_eq!(dec("Zg==="), Err(Base64Error::InvalidPadding));
+        assert_eq!(dec("Z==="), Err(Base64Error::InvalidPadding));
+        assert_eq!(dec("Zg==Zg=="), Err(Base64Error::InvalidPadding));
+        assert_eq!(dec("Zm9vY"), Err(Base64Error::InvalidPadding));
+
+        // 4095 bytes are exactly 5460 characters
+        let full = "AAAA".repeat(1365);
+        assert_eq!(dec(&full).unwrap().len(), 4095);
+        let over = Err(Base64Error::CapacityOverflow(CapacityError {
+            requested: 4096,
+            limit: 4095,
+        }));
+        assert_eq!(dec(&(full.clone() + "AA==")).map(|v| v.len()), over);
+        assert_eq!(
+            C::from_base64_url_left(&(full + "AA")).map(|c| c.len_left()),
+            over.map(Some)
+        );
+    }
+
+    #[test]
+    fn from_hex() {
+        let c = C::from_hex_left("000fA5ff").unwrap();
+        assert!(c == C::from(&[0x00, 0x0f, 0xa5, 0xff][..]));
+        assert_eq!(c.to_hex_left(), "000fa5ff");
+        assert!(C::from_hex_left("").unwrap() == C::new_left());
+        assert_eq!(C::from_hex_left("abc").err(), Some(HexError::OddLength));
+        assert_eq!(
+            C::from_hex_left("0g").err(),
+            Some(HexError::InvalidChar('g'))
+        );
+        assert_eq!(
+            C::from_hex_left("\u{e9}").err(),
+            Some(HexError::InvalidChar('\u{e9}'))
+        );
+
+        let full = "ab".repeat(4095);
+        assert_eq!(C::from_hex_left(&full).unwrap().len_left(), Some(4095));
+        assert_eq!(
+            C::from_hex_left(&(full + "ab")).err(),
+            Some(HexError::CapacityOverflow(CapacityError {
+                requested: 4096,
+                limit: 4095
+            }))
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Simple non-cryptographic checksums of raw bytes.
+
+/// The multiplier of the FxHash (as used by rustc).
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+fn fx_add(hash: u64, word: u64) -> u64 {
+    (hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED)
+}
+
+/// FxHash of the bytes, processed by 8-byte words.
+///
+/// The length is mixed in at the end, so zero bytes at the end are not lost in the padding of the
+/// last word.
+pub(crate) fn fx_hash(bytes: &[u8]) -> u64 {
+    let mut chunks = bytes.chunks_exact(8);
+    let mut hash = 0;
+    for chunk in &mut chunks {
+        let mut word = [0; 8];
+        word.copy_from_slice(chunk);
+        hash = fx_add(hash, u64::from_le_bytes(word));
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        let mut word = [0; 8];
+        word[..rest.len()].copy_from_slice(rest);
+        hash = fx_add(hash, u64::from_le_bytes(word));
+    }
+    fx_add(hash, bytes.len() as u64)
+}
+
+/// The largest prime below 2^16.
+const ADLER_MOD: u32 = 65521;
+
+/// The Adler-32 checksum (as used by zlib).
+pub(crate) fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    // The sums can't overflow within 5552 bytes, so the modulo is needed only once per chunk.
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= ADLER_MOD;
+        b %= ADLER_MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fx_hash_distinguishes() {
+        assert_eq!(fx_hash(b"hello world"), fx_hash(b"hello world"));
+        assert_ne!(fx_hash(b"hello world"), fx_hash(b"hello_world"));
+        assert_ne!(fx_hash(b""), fx_hash(b"\0"));
+        assert_ne!(fx_hash(b"\0"), fx_hash(b"\0\0"));
+        assert_ne!(fx_hash(&[0; 8]), fx_hash(&[0; 9]));
+    }
+
+    #[test]
+    fn adler32_known() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        // Long enough to need the modulo in between
+        assert_eq!(adler32(&[0xff; 10_000]), 0xb623_eb2b);
+    }
+}
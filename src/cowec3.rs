@@ -0,0 +1,172 @@
+//! A three-variant version of [`CoWec`][crate::CoWec].
+
+use core::marker::PhantomData;
+
+use super::{CoWecBlock, RefCnt};
+
+/// Mask of the tag bits in the pointer.
+const TAG_MASK: usize = 0b11;
+/// The tag of the left variant.
+const LEFT: usize = 0b00;
+/// The tag of the right variant (the same as in [`CoWec`][crate::CoWec]).
+const RIGHT: usize = 0b01;
+/// The tag of the middle variant.
+const MIDDLE: usize = 0b10;
+
+/// Like [`CoWec`][crate::CoWec], but with three variants.
+///
+/// As the blocks are aligned to 4 bytes, there are two free bits at the bottom of the pointer to
+/// use as the tag:
+///
+/// * Stub: the whole pointer is 0.
+/// * Left (`T`): the tag is `0b00` (and the pointer is non-zero).
+/// * Middle (`U`): the tag is `0b10`.
+/// * Right (`V`): the tag is `0b01`, the same as right in [`CoWec`][crate::CoWec]. The tag `0b11`
+///   is not used.
+#[repr(transparent)]
+pub struct CoWec3<R, T, U, V>
+where
+    R: RefCnt,
+{
+    ptr: usize,
+    _l: PhantomData<*mut CoWecBlock<R, T>>,
+    _m: PhantomData<*mut CoWecBlock<R, U>>,
+    _r: PhantomData<*mut CoWecBlock<R, V>>,
+}
+
+impl<R, T, U, V> CoWec3<R, T, U, V>
+where
+    R: RefCnt,
+{
+    fn from_ptr(ptr: usize) -> Self {
+        Self {
+            ptr,
+            _l: PhantomData,
+            _m: PhantomData,
+            _r: PhantomData,
+        }
+    }
+
+    fn tag(&self) -> usize {
+        self.ptr & TAG_MASK
+    }
+
+    fn untagged(&self) -> usize {
+        self.ptr & !TAG_MASK
+    }
+
+    pub fn new_stub() -> Self {
+        Self::from_ptr(0)
+    }
+
+    pub fn new_left() -> Self {
+        let l = unsafe { CoWecBlock::<R, T>::create(2) };
+        Self::from_ptr(l as usize | LEFT)
+    }
+
+    pub fn new_middle() -> Self {
+        let m = unsafe { CoWecBlock::<R, U>::create(2) };
+        Self::from_ptr(m as usize | MIDDLE)
+    }
+
+    pub fn new_right() -> Self {
+        let r = unsafe { CoWecBlock::<R, V>::create(2) };
+        Self::from_ptr(r as usize | RIGHT)
+    }
+
+    pub fn is_stub(&self) -> bool {
+        self.ptr == 0
+    }
+
+    pub fn is_left(&self) -> bool {
+        self.tag() == LEFT && !self.is_stub()
+    }
+
+    pub fn is_middle(&self) -> bool {
+        self.tag() == MIDDLE
+    }
+
+    pub fn is_right(&self) -> bool {
+        self.tag() == RIGHT
+    }
+}
+
+impl<R, T, U, V> Clone for CoWec3<R, T, U, V>
+where
+    R: RefCnt,
+{
+    fn clone(&self) -> Self {
+        let untagged = self.untagged();
+        let ptr = if self.is_left() {
+            unsafe { CoWecBlock::<R, T>::inc_ref(untagged as *mut _) as usize | LEFT }
+        } else if self.is_middle() {
+            unsafe { CoWecBlock::<R, U>::inc_ref(untagged as *mut _) as usize | MIDDLE }
+        } else if self.is_right() {
+            unsafe { CoWecBlock::<R, V>::inc_ref(untagged as *mut _) as usize | RIGHT }
+        } else {
+            0
+        };
+        Self::from_ptr(ptr)
+    }
+}
+
+impl<R, T, U, V> Drop for CoWec3<R, T, U, V>
+where
+    R: RefCnt,
+{
+    fn drop(&mut self) {
+        let untagged = self.untagged();
+        if self.is_left() {
+            unsafe { CoWecBlock::<R, T>::dec_ref(untagged as *mut _) }
+        } else if self.is_middle() {
+            unsafe { CoWecBlock::<R, U>::dec_ref(untagged as *mut _) }
+        } else if self.is_right() {
+            unsafe { CoWecBlock::<R, V>::dec_ref(untagged as *mut _) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec3<RCell, String, u8, Vec<u8>>;
+
+    #[test]
+    fn block_alignment() {
+        assert!(mem::align_of::<CoWecBlock<RCell, u8>>() > TAG_MASK);
+        assert!(mem::align_of::<CoWecBlock<crate::AtomicRCell, u8>>() > TAG_MASK);
+    }
+
+    #[test]
+    fn variants() {
+        let s = C::new_stub();
+        assert!(s.is_stub() && !s.is_left() && !s.is_middle() && !s.is_right());
+        let l = C::new_left();
+        assert!(!l.is_stub() && l.is_left() && !l.is_middle() && !l.is_right());
+        let m = C::new_middle();
+        assert!(!m.is_stub() && !m.is_left() && m.is_middle() && !m.is_right());
+        let r = C::new_right();
+        assert!(!r.is_stub() && !r.is_left() && !r.is_middle() && r.is_right());
+    }
+
+    #[test]
+    fn clone() {
+        for c in &[
+            C::new_stub(),
+            C::new_left(),
+            C::new_middle(),
+            C::new_right(),
+        ] {
+            let d = c.clone();
+            assert_eq!(c.ptr, d.ptr);
+            drop(d);
+            let d = c.clone();
+            drop(d);
+        }
+    }
+}
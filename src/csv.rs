@@ -0,0 +1,158 @@
+//! Conversions to and from rows of separated values.
+
+use alloc::string::{String, ToString};
+
+use super::{CapacityError, CoWec, CoWecBlock, GrowthPolicy, LenCap, ParseError, RefCnt};
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Parses a row of comma-separated values into a left `CoWec`, converting each field by the
+    /// `parser`.
+    ///
+    /// The fields are taken verbatim, there's no quoting or escaping (therefore they can't
+    /// contain commas) and no whitespace trimming. An empty row produces an empty `CoWec`, not one
+    /// with a single empty field.
+    ///
+    /// # Errors
+    ///
+    /// If the `parser` rejects a field (the first one that fails is reported) or if there are more
+    /// fields than fit into a block.
+    pub fn from_csv_row_left<P>(row: &str, parser: P) -> Result<Self, ParseError>
+    where
+        P: Fn(&str) -> Option<T>,
+    {
+        Self::from_row_left(row, ',', parser)
+    }
+
+    /// Formats the left elements by `format` and joins them with commas.
+    ///
+    /// The counterpart of [`from_csv_row_left`][CoWec::from_csv_row_left], with the same
+    /// limitations (the formatted values are not quoted or escaped). The other variants produce
+    /// an empty string.
+    pub fn to_csv_row_left<F>(&self, format: F) -> String
+    where
+        F: Fn(&T) -> String,
+    {
+        self.to_row_left(',', format)
+    }
+
+    /// Formats the left elements by `format` and joins them with tabs.
+    ///
+    /// Like [`to_csv_row_left`][CoWec::to_csv_row_left], only with a different separator.
+    pub fn to_tsv_row_left<F>(&self, format: F) -> String
+    where
+        F: Fn(&T) -> String,
+    {
+        self.to_row_left('\t', format)
+    }
+
+    fn from_row_left<P>(row: &str, separator: char, parser: P) -> Result<Self, ParseError>
+    where
+        P: Fn(&str) -> Option<T>,
+    {
+        if row.is_empty() {
+            return Ok(Self::new_left());
+        }
+        let fields = row.matches(separator).count() + 1;
+        let limit = CoWecBlock::<R, T, L>::LEN_MASK;
+        if fields > limit {
+            return Err(ParseError::CapacityOverflow(CapacityError {
+                requested: fields,
+                limit,
+            }));
+        }
+        let block = unsafe { CoWecBlock::<R, T, L>::create_for(fields) };
+        // Owns the partially filled block if we bail out early
+        let me = Self::from_left_block(block);
+        for (idx, field) in row.split(separator).enumerate() {
+            let val =
+                parser(field).ok_or_else(|| ParseError::InvalidField(idx, field.to_string()))?;
+            unsafe { CoWecBlock::insert(block, idx, val) };
+        }
+        Ok(me)
+    }
+
+    fn to_row_left<F>(&self, separator: char, format: F) -> String
+    where
+        F: Fn(&T) -> String,
+    {
+        let mut row = String::new();
+        for (idx, elem) in self.left_slice().unwrap_or_default().iter().enumerate() {
+            if idx > 0 {
+                row.push(separator);
+            }
+            row.push_str(&format(elem));
+        }
+        row
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, i32, ()>;
+
+    fn parse(row: &str) -> Result<Vec<i32>, ParseError> {
+        C::from_csv_row_left(row, |f| f.parse().ok()).map(Vec::from)
+    }
+
+    #[test]
+    fn from_csv() {
+        assert_eq!(parse("1,-2,3"), Ok(vec![1, -2, 3]));
+        assert_eq!(parse("42"), Ok(vec![42]));
+        assert_eq!(parse(""), Ok(vec![]));
+        assert_eq!(
+            parse("1,x,y"),
+            Err(ParseError::InvalidField(1, "x".to_string()))
+        );
+        assert_eq!(
+            parse("1, 2"),
+            Err(ParseError::InvalidField(1, " 2".to_string()))
+        );
+        assert_eq!(
+            parse("1,2,"),
+            Err(ParseError::InvalidField(2, String::new()))
+        );
+        let strings = CoWec::<RCell, String, ()>::from_csv_row_left("a,,b", |f| Some(f.into()));
+        assert_eq!(Vec::from(strings.unwrap()), ["a", "", "b"]);
+
+        let full = vec!["1"; 4095].join(",");
+        assert_eq!(parse(&full), Ok(vec![1; 4095]));
+        assert_eq!(
+            parse(&(full + ",1")),
+            Err(ParseError::CapacityOverflow(CapacityError {
+                requested: 4096,
+                limit: 4095
+            }))
+        );
+    }
+
+    #[test]
+    fn to_csv() {
+        let format = |v: &i32| v.to_string();
+        assert_eq!(C::from(&[1, -2, 3][..]).to_csv_row_left(format), "1,-2,3");
+        assert_eq!(C::from(&[7][..]).to_csv_row_left(format), "7");
+        assert_eq!(C::new_left().to_csv_row_left(format), "");
+        assert_eq!(C::new_right().to_csv_row_left(format), "");
+        for row in ["1,2,3", "-5", ""] {
+            let c = C::from_csv_row_left(row, |f| f.parse().ok()).unwrap();
+            assert_eq!(c.to_csv_row_left(format), row);
+        }
+    }
+
+    #[test]
+    fn to_tsv() {
+        let format = |v: &i32| v.to_string();
+        assert_eq!(C::from(&[1, -2, 3][..]).to_tsv_row_left(format), "1\t-2\t3");
+        assert_eq!(C::from(&[7][..]).to_tsv_row_left(format), "7");
+        assert_eq!(C::new_stub().to_tsv_row_left(format), "");
+    }
+}
@@ -0,0 +1,204 @@
+//! Cursors for walking the left variant.
+
+use core::marker::PhantomData;
+
+use super::{CoWec, GrowthPolicy, LenCap, RefCnt, SeekError};
+
+/// Checks the position for seeking within `len` elements.
+fn check_seek(pos: usize, len: usize) -> Result<usize, SeekError> {
+    if pos <= len {
+        Ok(pos)
+    } else {
+        Err(SeekError { pos, len })
+    }
+}
+
+/// A read-only cursor over the left variant.
+///
+/// It is cheap to copy and can move in both directions. Created by [`CoWec::cursor_left`].
+pub struct CoWecCursorLeft<'a, R: RefCnt, T, U, L: LenCap = u16> {
+    elems: &'a [T],
+    pos: usize,
+    _cowec: PhantomData<&'a CoWec<R, T, U, L>>,
+}
+
+impl<'a, R: RefCnt, T, U, L: LenCap> CoWecCursorLeft<'a, R, T, U, L> {
+    /// The current position (the index of the element [`peek`][CoWecCursorLeft::peek] returns).
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The element at the current position, if not at the end.
+    pub fn peek(&self) -> Option<&'a T> {
+        self.elems.get(self.pos)
+    }
+
+    /// Moves one element forward (unless already at the end).
+    pub fn advance(&mut self) -> &mut Self {
+        self.pos = (self.pos + 1).min(self.elems.len());
+        self
+    }
+
+    /// Returns the current element and moves past it.
+    #[allow(clippy::should_implement_trait)] // Not an iterator, it is not consumed by walking
+    pub fn next(&mut self) -> Option<&'a T> {
+        let elem = self.peek()?;
+        self.pos += 1;
+        Some(elem)
+    }
+
+    /// The number of elements from the current position to the end.
+    pub fn remaining(&self) -> usize {
+        self.elems.len() - self.pos
+    }
+
+    /// Moves to the position, in either direction.
+    ///
+    /// # Errors
+    ///
+    /// If the position is past the end (the end itself is allowed). The cursor doesn't move.
+    pub fn seek(&mut self, pos: usize) -> Result<(), SeekError> {
+        self.pos = check_seek(pos, self.elems.len())?;
+        Ok(())
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap> Clone for CoWecCursorLeft<'_, R, T, U, L> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap> Copy for CoWecCursorLeft<'_, R, T, U, L> {}
+
+/// A cursor over the left variant allowing modification.
+///
+/// Created by [`CoWec::cursor_left_mut`], which makes sure the block is not shared up front.
+pub struct CoWecCursorLeftMut<'a, R: RefCnt, T, U, L: LenCap = u16> {
+    elems: &'a mut [T],
+    pos: usize,
+    _cowec: PhantomData<&'a mut CoWec<R, T, U, L>>,
+}
+
+impl<R: RefCnt, T, U, L: LenCap> CoWecCursorLeftMut<'_, R, T, U, L> {
+    /// The current position.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The element at the current position, if not at the end.
+    pub fn peek(&self) -> Option<&T> {
+        self.elems.get(self.pos)
+    }
+
+    /// The element at the current position for modification, if not at the end.
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.elems.get_mut(self.pos)
+    }
+
+    /// Moves one element forward (unless already at the end).
+    pub fn advance(&mut self) -> &mut Self {
+        self.pos = (self.pos + 1).min(self.elems.len());
+        self
+    }
+
+    /// Returns the current element for modification and moves past it.
+    #[allow(clippy::should_implement_trait)] // Can't be an iterator, the items borrow the cursor
+    pub fn next(&mut self) -> Option<&mut T> {
+        let pos = self.pos;
+        self.advance();
+        self.elems.get_mut(pos)
+    }
+
+    /// Moves to the position, in either direction.
+    ///
+    /// # Errors
+    ///
+    /// If the position is past the end (the end itself is allowed). The cursor doesn't move.
+    pub fn seek(&mut self, pos: usize) -> Result<(), SeekError> {
+        self.pos = check_seek(pos, self.elems.len())?;
+        Ok(())
+    }
+}
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Creates a cursor at the start of the left variant.
+    ///
+    /// For the other variants, the cursor is empty.
+    pub fn cursor_left(&self) -> CoWecCursorLeft<'_, R, T, U, L> {
+        CoWecCursorLeft {
+            elems: self.left_slice().unwrap_or_default(),
+            pos: 0,
+            _cowec: PhantomData,
+        }
+    }
+
+    /// Creates a modifying cursor at the start of the left variant.
+    ///
+    /// The block is copied first if it is shared. For the other variants, the cursor is empty.
+    pub fn cursor_left_mut(&mut self) -> CoWecCursorLeftMut<'_, R, T, U, L>
+    where
+        T: Clone,
+    {
+        CoWecCursorLeftMut {
+            elems: self.left_slice_mut().unwrap_or_default(),
+            pos: 0,
+            _cowec: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, u32, ()>;
+
+    #[test]
+    fn cursor() {
+        let c = C::from(vec![1, 2, 3]);
+        let mut cursor = c.cursor_left();
+        assert_eq!(cursor.peek(), Some(&1));
+        assert_eq!(cursor.advance().peek(), Some(&2));
+        let copy = cursor;
+        assert_eq!(cursor.next(), Some(&2));
+        assert_eq!(cursor.next(), Some(&3));
+        assert_eq!(cursor.next(), None);
+        assert_eq!(copy.position(), 1);
+        assert_eq!(copy.remaining(), 2);
+
+        let mut cursor = c.cursor_left();
+        cursor.seek(3).unwrap();
+        assert_eq!(cursor.peek(), None);
+        assert_eq!(cursor.advance().position(), 3);
+        assert_eq!(cursor.seek(4), Err(SeekError { pos: 4, len: 3 }));
+        cursor.seek(0).unwrap();
+        assert_eq!(cursor.peek(), Some(&1));
+
+        assert_eq!(C::new_right().cursor_left().next(), None);
+    }
+
+    #[test]
+    fn cursor_mut() {
+        let mut c = C::from(vec![1, 2, 3]);
+        let shared = c.clone();
+        let mut cursor = c.cursor_left_mut();
+        *cursor.next().unwrap() = 10;
+        *cursor.peek_mut().unwrap() = 20;
+        cursor.seek(0).unwrap();
+        assert_eq!(cursor.peek(), Some(&10));
+        cursor.seek(3).unwrap();
+        assert!(cursor.next().is_none());
+        assert_eq!(c.left_slice(), Some(&[10, 20, 3][..]));
+        assert_eq!(shared.left_slice(), Some(&[1, 2, 3][..]));
+        assert!(C::new_stub().cursor_left_mut().peek().is_none());
+    }
+}
@@ -0,0 +1,393 @@
+//! Element-level diffs between left variants.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{CapacityError, CoWec, CoWecBlock, DiffError, GrowthPolicy, LenCap, RefCnt};
+
+/// A single step of a diff, describing how to turn one sequence into another.
+///
+/// Produced by [`CoWec::diff_left`]. The steps are applied in order, going through the original
+/// sequence from its start.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Diff<T> {
+    /// This many next elements of the original stay.
+    Keep(usize),
+    /// A new element is inserted.
+    Insert(T),
+    /// The next element of the original is removed.
+    Remove(T),
+}
+
+/// Appends a run of kept elements, merging it with the previous one.
+fn keep<T>(diff: &mut Vec<Diff<T>>, n: usize) {
+    if n == 0 {
+        return;
+    }
+    match diff.last_mut() {
+        Some(Diff::Keep(run)) => *run += n,
+        _ => diff.push(Diff::Keep(n)),
+    }
+}
+
+/// The lengths of the LCS of all of `b` and each prefix of `a` (`a_len + 1` of them).
+fn lcs_row<'a, T, B, A>(b: B, a: A, a_len: usize) -> Vec<usize>
+where
+    T: PartialEq + 'a,
+    B: Iterator<Item = &'a T>,
+    A: Iterator<Item = &'a T> + Clone,
+{
+    let mut row = vec![0; a_len + 1];
+    for x in b {
+        // The value of row[j] from the previous round
+        let mut diag = 0;
+        for (j, y) in a.clone().enumerate() {
+            let up = row[j + 1];
+            row[j + 1] = if x == y { diag + 1 } else { up.max(row[j]) };
+            diag = up;
+        }
+    }
+    row
+}
+
+/// Hirschberg's algorithm, keeping only two rows of the LCS table at a time.
+fn diff_into<T: PartialEq + Clone>(diff: &mut Vec<Diff<T>>, b: &[T], a: &[T]) {
+    match b {
+        [] => diff.extend(a.iter().cloned().map(Diff::Insert)),
+        _ if a.is_empty() => diff.extend(b.iter().cloned().map(Diff::Remove)),
+        [x] => match a.iter().position(|y| x == y) {
+            Some(j) => {
+                diff.extend(a[..j].iter().cloned().map(Diff::Insert));
+                keep(diff, 1);
+                diff.extend(a[j + 1..].iter().cloned().map(Diff::Insert));
+            }
+            None => {
+                diff.extend(a.iter().cloned().map(Diff::Insert));
+                diff.push(Diff::Remove(x.clone()));
+            }
+        },
+        _ => {
+            let mid = b.len() / 2;
+            let front = lcs_row(b[..mid].iter(), a.iter(), a.len());
+            let back = lcs_row(b[mid..].iter().rev(), a.iter().rev(), a.len());
+            let split = (0..=a.len())
+                .max_by_key(|&k| (front[k] + back[a.len() - k], k))
+                .unwrap();
+            diff_into(diff, &b[..mid], &a[..split]);
+            diff_into(diff, &b[mid..], &a[split..]);
+        }
+    }
+}
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Computes the diff turning the left elements of `before` into those of `after`.
+    ///
+    /// The diff is minimal (based on the longest common subsequence), with the unchanged runs
+    /// compressed into single [`Keep`][Diff::Keep] steps. Variants other than left are considered
+    /// empty. The computation takes `O(n·m)` time but only linear memory, where the common prefix
+    /// and suffix don't count into `n` and `m`.
+    ///
+    /// # Errors
+    ///
+    /// If the diff has more steps than fit into a block. This can happen only if most of the
+    /// elements differ.
+    pub fn diff_left(
+        before: &Self,
+        after: &Self,
+    ) -> Result<CoWec<R, Diff<T>, U, L, G>, CapacityError>
+    where
+        T: PartialEq + Clone,
+    {
+        let before = before.left_slice().unwrap_or_default();
+        let after = after.left_slice().unwrap_or_default();
+        let prefix = before.iter().zip(after).take_while(|(b, a)| b == a).count();
+        let (before_rest, after_rest) = (&before[prefix..], &after[prefix..]);
+        let suffix = before_rest
+            .iter()
+            .rev()
+            .zip(after_rest.iter().rev())
+            .take_while(|(b, a)| b == a)
+            .count();
+        let b = &before_rest[..before_rest.len() - suffix];
+        let a = &after_rest[..after_rest.len() - suffix];
+
+        let mut diff = Vec::new();
+        keep(&mut diff, prefix);
+        diff_into(&mut diff, b, a);
+        keep(&mut diff, suffix);
+        let limit = CoWecBlock::<R, Diff<T>, L>::LEN_MASK;
+        if diff.len() > limit {
+            return Err(CapacityError {
+                requested: diff.len(),
+                limit,
+            });
+        }
+        Ok(CoWec::from(diff))
+    }
+
+    /// Applies a diff (as produced by [`diff_left`][CoWec::diff_left]) to the left elements.
+    ///
+    /// # Errors
+    ///
+    /// If either `self` or the `diff` is not the left variant, if the diff doesn't match the
+    /// current elements or if the result doesn't fit into a block. In such case, `self` is left
+    /// unchanged.
+    pub fn apply_diff_left(&mut self, diff: &CoWec<R, Diff<T>, U, L, G>) -> Result<(), DiffError>
+    where
+        T: PartialEq + Clone,
+    {
+        let orig = self.left_slice().ok_or(DiffError::WrongVariant)?;
+        let diff = diff.left_slice().ok_or(DiffError::WrongVariant)?;
+        let mut result = Vec::with_capacity(orig.len());
+        let mut pos = 0;
+        for step in diff {
+            match step {
+                Diff::Keep(n) => {
+                    let kept = orig[pos..].get(..*n).ok_or(DiffError::OutOfBounds)?;
+                    result.extend_from_slice(kept);
+                    pos += n;
+                }
+                Diff::Insert(val) => result.push(val.clone()),
+                Diff::Remove(val) => {
+                    if orig.get(pos).ok_or(DiffError::OutOfBounds)? != val {
+                        return Err(DiffError::Mismatch { pos });
+                    }
+                    pos += 1;
+                }
+            }
+        }
+        if pos != orig.len() {
+            return Err(DiffError::Incomplete);
+        }
+        let limit = CoWecBlock::<R, T, L>::LEN_MASK;
+        if result.len() > limit {
+            return Err(DiffError::CapacityOverflow(CapacityError {
+                requested: result.len(),
+                limit,
+            }));
+        }
+        *self = Self::from(result);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, char, ()>;
+
+    fn diff(before: &str, after: &str) -> Vec<Diff<char>> {
+        let before = C::from(before.chars().collect::<Vec<_>>());
+        let after = C::from(after.chars().collect::<Vec<_>>());
+        C::diff_left(&before, &after)
+            .unwrap()
+            .left_slice()
+            .unwrap()
+            .to_vec()
+    }
+
+    /// Applies the diff, to check it produces the right result.
+    fn patch(before: &str, diff: &[Diff<char>]) -> String {
+        let mut before = before.chars();
+        let mut result = String::new();
+        for step in diff {
+            match step {
+                Diff::Keep(n) => result.extend(before.by_ref().take(*n)),
+                Diff::Insert(c) => result.push(*c),
+                Diff::Remove(c) => assert_eq!(before.next(), Some(*c)),
+            }
+        }
+        assert_eq!(before.next(), None);
+        result
+    }
+
+    #[test]
+    fn simple() {
+        use Diff::*;
+        assert_eq!(diff("", ""), []);
+        assert_eq!(diff("abc", "abc"), [Keep(3)]);
+        assert_eq!(diff("", "ab"), [Insert('a'), Insert('b')]);
+        assert_eq!(diff("ab", ""), [Remove('a'), Remove('b')]);
+        assert_eq!(
+            diff("abc", "axc"),
+            [Keep(1), Insert('x'), Remove('b'), Keep(1)]
+        );
+        assert_eq!(
+            diff("abcd", "ad"),
+            [Keep(1), Remove('b'), Remove('c'), Keep(1)]
+        );
+    }
+
+    /// The number of elements kept by the diff.
+    fn kept(diff: &[Diff<char>]) -> usize {
+        diff.iter()
+            .map(|s| match s {
+                Diff::Keep(n) => *n,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[test]
+    fn minimal() {
+        let cases = [
+            ("kitten", "sitting"),
+            ("abcabba", "cbabac"),
+            ("hello world", "yellow word"),
+            ("aaaa", "aa"),
+            ("xyz", "zyx"),
+        ];
+        for (before, after) in cases {
+            let d = diff(before, after);
+            assert_eq!(patch(before, &d), after);
+            let kept = kept(&d);
+            // The sum of lengths minus the edits is twice the LCS
+            let edits = d.iter().filter(|s| !matches!(s, Diff::Keep(_))).count();
+            assert_eq!(before.len() + after.len() - edits, 2 * kept);
+            // The runs of kept elements are merged
+            assert!(d
+                .windows(2)
+                .all(|w| !matches!(w, [Diff::Keep(_), Diff::Keep(_)])));
+        }
+        // Known LCS lengths
+        let kept = |b, a| kept(&diff(b, a));
+        assert_eq!(kept("kitten", "sitting"), 4);
+        assert_eq!(kept("abcabba", "cbabac"), 4);
+    }
+
+    #[test]
+    fn apply() {
+        let cases = [
+            ("kitten", "sitting"),
+            ("", "abc"),
+            ("abc", ""),
+            ("same", "same"),
+        ];
+        for (before, after) in cases {
+            let mut before = C::from(before.chars().collect::<Vec<_>>());
+            let after = C::from(after.chars().collect::<Vec<_>>());
+            let d = C::diff_left(&before, &after).unwrap();
+            before.apply_diff_left(&d).unwrap();
+            assert!(before == after);
+        }
+    }
+
+    #[test]
+    fn apply_errors() {
+        use Diff::*;
+        let apply = |steps: Vec<Diff<char>>| {
+            let mut c = C::from(vec!['a', 'b']);
+            let result = c.apply_diff_left(&CoWec::from(steps));
+            if result.is_err() {
+                assert!(c == C::from(vec!['a', 'b']));
+            }
+            result
+        };
+        assert_eq!(apply(vec![Keep(1), Remove('b'), Insert('c')]), Ok(()));
+        assert_eq!(apply(vec![Keep(0), Keep(2)]), Ok(()));
+        assert_eq!(
+            apply(vec![Keep(1), Remove('x')]),
+            Err(DiffError::Mismatch { pos: 1 })
+        );
+        assert_eq!(apply(vec![Keep(3)]), Err(DiffError::OutOfBounds));
+        assert_eq!(apply(vec![Keep(2), Keep(1)]), Err(DiffError::OutOfBounds));
+        assert_eq!(apply(vec![Keep(usize::MAX)]), Err(DiffError::OutOfBounds));
+        assert_eq!(
+            apply(vec![Remove('a'), Remove('b'), Remove('c')]),
+            Err(DiffError::OutOfBounds)
+        );
+        assert_eq!(apply(vec![Keep(1)]), Err(DiffError::Incomplete));
+        let mut stub = C::new_stub();
+        assert_eq!(
+            stub.apply_diff_left(&CoWec::new_left()),
+            Err(DiffError::WrongVariant)
+        );
+        let mut c = C::new_left();
+        assert_eq!(
+            c.apply_diff_left(&CoWec::new_right()),
+            Err(DiffError::WrongVariant)
+        );
+    }
+
+    #[test]
+    fn other_variants_empty() {
+        let after = C::from(vec!['a']);
+        let d = C::diff_left(&C::new_right(), &after).unwrap();
+        assert_eq!(d.left_slice(), Some(&[Diff::Insert('a')][..]));
+        let d = C::diff_left(&after, &C::new_stub()).unwrap();
+        assert_eq!(d.left_slice(), Some(&[Diff::Remove('a')][..]));
+    }
+
+    #[test]
+    fn full_blocks() {
+        type N = CoWec<RCell, u16, ()>;
+        let before: Vec<u16> = (0..4095).collect();
+        let mut after = before.clone();
+        after[2000] = 9999;
+        let (mut before, after) = (N::from(before), N::from(after));
+        let d = N::diff_left(&before, &after).unwrap();
+        assert_eq!(
+            d.left_slice().unwrap(),
+            [
+                Diff::Keep(2000),
+                Diff::Insert(9999),
+                Diff::Remove(2000),
+                Diff::Keep(2094)
+            ]
+        );
+        before.apply_diff_left(&d).unwrap();
+        assert!(before == after);
+
+        // Changes spread all over, so the middle part is not trimmed away
+        let mut before: Vec<u16> = (0..4095).collect();
+        before[0] = 9999;
+        before[4094] = 9999;
+        let after: Vec<u16> = (0..4095)
+            .map(|i| if i % 100 == 50 { 1 } else { i })
+            .collect();
+        let (mut before, after) = (N::from(before), N::from(after));
+        let d = N::diff_left(&before, &after).unwrap();
+        // An insert and a remove for each replaced element: both ends and 41 in the middle
+        let edits = d
+            .left_slice()
+            .unwrap()
+            .iter()
+            .filter(|s| !matches!(s, Diff::Keep(_)))
+            .count();
+        assert_eq!(edits, 4 + 2 * 41);
+        before.apply_diff_left(&d).unwrap();
+        assert!(before == after);
+
+        // Nothing in common, so there's an insert and remove for each element
+        let before: N = (0..4095).collect::<Vec<_>>().into();
+        let after: N = (5000..9095).collect::<Vec<_>>().into();
+        assert_eq!(
+            N::diff_left(&before, &after).err().unwrap(),
+            CapacityError {
+                requested: 8190,
+                limit: 4095
+            }
+        );
+
+        // The diff fits, but the result of applying it to a full block doesn't
+        let mut full: N = (0..4095).collect::<Vec<_>>().into();
+        let d = CoWec::from(vec![Diff::Keep(4095), Diff::Insert(0)]);
+        assert_eq!(
+            full.apply_diff_left(&d),
+            Err(DiffError::CapacityOverflow(CapacityError {
+                requested: 4096,
+                limit: 4095
+            }))
+        );
+        assert_eq!(full.len_left(), Some(4095));
+    }
+}
@@ -0,0 +1,132 @@
+//! Tracking of modifications with a dirty flag.
+
+use alloc::rc::Rc;
+use core::cell::Cell;
+use core::ops::{Deref, DerefMut};
+
+use super::{ChangeListener, CoWec, DoublingGrowth, GrowthPolicy, LenCap, RefCnt};
+
+/// A wrapper around a [`CoWec`] that remembers if it was modified since it was last marked clean.
+///
+/// This is a [`ChangeListener`] with a callback that sets the flag, the modifications are the
+/// methods of the listener (available through [`DerefMut`]) and the same ones count as changes.
+/// Read-only access to the `CoWec` goes through [`Deref`] as well.
+///
+/// This is not the same as the block being shared or not. A freshly copied block is not dirty
+/// until something is actually changed in it and a unique block may have been modified. Cloning
+/// the wrapper clones the flag too, both copies start with the original state (but only the
+/// flag, not any other callbacks registered on the listener).
+pub struct DirtyCoWec<R: RefCnt, T, U, L: LenCap = u16, G: GrowthPolicy = DoublingGrowth> {
+    listener: ChangeListener<R, T, U, L, G>,
+    dirty: Rc<Cell<bool>>,
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> DirtyCoWec<R, T, U, L, G> {
+    /// Wraps the `CoWec`, starting clean.
+    pub fn new(cowec: CoWec<R, T, U, L, G>) -> Self {
+        Self::with_flag(cowec, false)
+    }
+
+    fn with_flag(cowec: CoWec<R, T, U, L, G>, dirty: bool) -> Self {
+        let dirty = Rc::new(Cell::new(dirty));
+        let mut listener = ChangeListener::new(cowec);
+        let flag = Rc::clone(&dirty);
+        listener.on_change(move || flag.set(true));
+        Self { listener, dirty }
+    }
+
+    /// Checks if there was a modification since the creation or the last
+    /// [`mark_clean`][DirtyCoWec::mark_clean].
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.get()
+    }
+
+    /// Resets the flag.
+    pub fn mark_clean(&mut self) {
+        self.dirty.set(false);
+    }
+
+    /// Unwraps the `CoWec`, dropping the flag.
+    pub fn into_inner(self) -> CoWec<R, T, U, L, G> {
+        self.listener.into_inner()
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Clone for DirtyCoWec<R, T, U, L, G> {
+    fn clone(&self) -> Self {
+        Self::with_flag(CoWec::clone(&self.listener), self.is_dirty())
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Deref for DirtyCoWec<R, T, U, L, G> {
+    type Target = ChangeListener<R, T, U, L, G>;
+
+    fn deref(&self) -> &ChangeListener<R, T, U, L, G> {
+        &self.listener
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> DerefMut for DirtyCoWec<R, T, U, L, G> {
+    fn deref_mut(&mut self) -> &mut ChangeListener<R, T, U, L, G> {
+        &mut self.listener
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InsertError, RCell};
+
+    type D = DirtyCoWec<RCell, u32, ()>;
+
+    #[test]
+    fn dirty() {
+        let mut d = D::new(CoWec::from(&[1, 2][..]));
+        assert!(!d.is_dirty());
+        assert_eq!(d.get_left(0), Some(&1));
+        assert!(!d.is_dirty());
+
+        d.push_left(3).unwrap();
+        assert!(d.is_dirty());
+        d.mark_clean();
+        assert!(!d.is_dirty());
+
+        let shared = d.clone();
+        assert!(!shared.is_dirty());
+        *d.get_left_mut(0).unwrap() = 10;
+        assert!(d.is_dirty());
+        assert!(!shared.is_dirty());
+        assert_eq!(shared.get_left(0), Some(&1));
+        assert!(d.clone().is_dirty());
+
+        let ops: [fn(&mut D); 5] = [
+            |d| d.insert_left(0, 0).unwrap(),
+            |d| assert!(d.pop_left().is_some()),
+            |d| assert_eq!(d.remove_left(0), 0),
+            |d| d.fill_left(7),
+            |d| d.modify(|_| ()),
+        ];
+        for op in &ops {
+            d.mark_clean();
+            op(&mut d);
+            assert!(d.is_dirty());
+        }
+        assert_eq!(d.into_inner().left_slice(), Some(&[7, 7][..]));
+    }
+
+    #[test]
+    fn failed_clean() {
+        let mut d = D::new(CoWec::new_right());
+        assert_eq!(d.push_left(1), Err(InsertError::WrongVariant));
+        assert_eq!(d.insert_left(0, 1), Err(InsertError::WrongVariant));
+        assert_eq!(d.pop_left(), None);
+        assert_eq!(d.get_left_mut(0), None);
+        d.fill_left(1);
+        assert!(!d.is_dirty());
+
+        // Nothing to overwrite in an empty one
+        let mut d = D::new(CoWec::new_left());
+        d.fill_left(1);
+        assert!(!d.is_dirty());
+    }
+}
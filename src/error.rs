@@ -0,0 +1,196 @@
+//! Error types.
+
+use alloc::string::String;
+use core::alloc::Layout;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+/// An error returned when inserting an element fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum InsertError {
+    /// The [`CoWec`][crate::CoWec] holds a different variant than the one inserted into.
+    WrongVariant,
+    /// The block already holds the maximum number of elements it can encode.
+    Full,
+}
+
+impl Display for InsertError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            InsertError::WrongVariant => write!(fmt, "Inserting into the wrong variant"),
+            InsertError::Full => write!(fmt, "The block is full"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InsertError {}
+
+/// An error returned when the requested length doesn't fit into a block.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CapacityError {
+    /// The length that was requested.
+    pub requested: usize,
+    /// The maximum length a block can hold.
+    pub limit: usize,
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(
+            fmt,
+            "Length {} over the limit of {}",
+            self.requested, self.limit
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+/// An error returned when parsing a hexadecimal string fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HexError {
+    /// The string has an odd number of digits.
+    OddLength,
+    /// The string contains a character that is not a hexadecimal digit.
+    InvalidChar(char),
+    /// The decoded bytes would be over the limit of a block.
+    CapacityOverflow(CapacityError),
+}
+
+impl Display for HexError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            HexError::OddLength => write!(fmt, "Odd number of hex digits"),
+            HexError::InvalidChar(c) => write!(fmt, "Invalid hex digit {:?}", c),
+            HexError::CapacityOverflow(e) => e.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+/// An error returned when decoding a Base64 string fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Base64Error {
+    /// The string contains a character outside of the alphabet.
+    InvalidChar(char),
+    /// The padding is misplaced or there's a wrong amount of it.
+    InvalidPadding,
+    /// The decoded bytes would be over the limit of a block.
+    CapacityOverflow(CapacityError),
+}
+
+impl Display for Base64Error {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            Base64Error::InvalidChar(c) => write!(fmt, "Invalid base64 character {:?}", c),
+            Base64Error::InvalidPadding => write!(fmt, "Invalid base64 padding"),
+            Base64Error::CapacityOverflow(e) => e.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Base64Error {}
+
+/// An error returned when reserving more capacity fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TryReserveError {
+    /// The [`CoWec`][crate::CoWec] holds a different variant than the one reserved in.
+    WrongVariant,
+    /// The requested length would be over the limit of a block.
+    CapacityOverflow(CapacityError),
+    /// The allocator failed to provide the memory.
+    AllocError(Layout),
+}
+
+impl Display for TryReserveError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            TryReserveError::WrongVariant => write!(fmt, "Reserving in the wrong variant"),
+            TryReserveError::CapacityOverflow(e) => e.fmt(fmt),
+            TryReserveError::AllocError(layout) => {
+                write!(fmt, "Failed to allocate {} bytes", layout.size())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryReserveError {}
+
+/// An error returned when a diff doesn't apply.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum DiffError {
+    /// The diff or the target is not the left variant.
+    WrongVariant,
+    /// The element removed by the diff at this position of the original doesn't match.
+    Mismatch {
+        /// The position in the original sequence.
+        pos: usize,
+    },
+    /// The diff reaches past the end of the original sequence.
+    OutOfBounds,
+    /// The diff doesn't cover the whole original sequence.
+    Incomplete,
+    /// The result would be over the limit of a block.
+    CapacityOverflow(CapacityError),
+}
+
+impl Display for DiffError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            DiffError::WrongVariant => write!(fmt, "Diff applied to or from the wrong variant"),
+            DiffError::Mismatch { pos } => write!(fmt, "Removed element at {} doesn't match", pos),
+            DiffError::OutOfBounds => write!(fmt, "Diff reaches past the end"),
+            DiffError::Incomplete => write!(fmt, "Diff doesn't cover the whole sequence"),
+            DiffError::CapacityOverflow(e) => e.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DiffError {}
+
+/// An error returned when seeking a cursor past the end.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SeekError {
+    /// The position that was requested.
+    pub pos: usize,
+    /// The number of elements (the largest position allowed).
+    pub len: usize,
+}
+
+impl Display for SeekError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        write!(fmt, "Position {} past the end at {}", self.pos, self.len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SeekError {}
+
+/// An error returned when parsing a row of separated values fails.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ParseError {
+    /// The field at this index (counted from 0) was rejected by the parser.
+    InvalidField(usize, String),
+    /// There are more fields than fit into a block.
+    CapacityOverflow(CapacityError),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        match self {
+            ParseError::InvalidField(idx, field) => {
+                write!(fmt, "Invalid field {:?} at index {}", field, idx)
+            }
+            ParseError::CapacityOverflow(e) => e.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
@@ -0,0 +1,68 @@
+//! Strategies for growing a block.
+
+/// Decides how much a block grows once it runs out of capacity.
+///
+/// The capacity of a block is encoded as a power of two (see [`LenCap`][crate::LenCap]),
+/// therefore whatever the policy returns is rounded up to one (and capped at the maximum length
+/// of a block). A policy returning less than `required` gets `required` instead.
+///
+/// The policy is a type parameter of [`CoWec`][crate::CoWec], defaulting to [`DoublingGrowth`].
+pub trait GrowthPolicy {
+    /// Computes the new capacity of a block currently holding `current_cap` slots that needs to
+    /// hold `required` elements (more than `current_cap`).
+    fn next_capacity(current_cap: usize, required: usize) -> usize;
+}
+
+/// Doubles the capacity (or more, if that's not enough).
+///
+/// The classical amortized strategy and the default.
+pub struct DoublingGrowth;
+
+impl GrowthPolicy for DoublingGrowth {
+    fn next_capacity(current_cap: usize, required: usize) -> usize {
+        current_cap.saturating_mul(2).max(required)
+    }
+}
+
+/// Grows by at least `STEP` slots at once.
+///
+/// Useful to skip the small sizes quickly. Once the capacity is larger than `STEP`, the rounding
+/// to a power of two makes it double the same as [`DoublingGrowth`].
+pub struct LinearGrowth<const STEP: usize>;
+
+impl<const STEP: usize> GrowthPolicy for LinearGrowth<STEP> {
+    fn next_capacity(current_cap: usize, required: usize) -> usize {
+        current_cap.saturating_add(STEP).max(required)
+    }
+}
+
+/// Grows only to what is required.
+///
+/// Meant for write-once data, where the final size is generally reserved up front. The grown
+/// capacities are still rounded up to a power of two, so this differs from [`DoublingGrowth`]
+/// only when the current capacity is not a power of two (a tight block after shrinking). It can
+/// still leave up to half of the block unused, use
+/// [`reserve_left_exact`][crate::CoWec::reserve_left_exact] to grow to exactly the required size
+/// or [`shrink_to_fit_left`][crate::CoWec::shrink_to_fit_left] to get rid of the spare room.
+pub struct ExactGrowth;
+
+impl GrowthPolicy for ExactGrowth {
+    fn next_capacity(_current_cap: usize, required: usize) -> usize {
+        required
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_capacity() {
+        assert_eq!(DoublingGrowth::next_capacity(4, 5), 8);
+        assert_eq!(DoublingGrowth::next_capacity(4, 20), 20);
+        assert_eq!(DoublingGrowth::next_capacity(usize::MAX, 5), usize::MAX);
+        assert_eq!(LinearGrowth::<10>::next_capacity(4, 5), 14);
+        assert_eq!(LinearGrowth::<10>::next_capacity(4, 20), 20);
+        assert_eq!(ExactGrowth::next_capacity(4, 5), 5);
+    }
+}
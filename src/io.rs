@@ -0,0 +1,304 @@
+//! The [`std::io`] traits for byte buffers and binary transfer of plain data.
+
+use alloc::vec;
+use core::convert::TryFrom;
+use core::{mem, ptr};
+use std::io;
+
+use super::{CapacityError, CoWec, CoWecBlock, ExactGrowth, GrowthPolicy, LenCap, RefCnt};
+
+/// How many bytes [`CoWec::recv_left`] reads at once (at least one element).
+const RECV_CHUNK: usize = 4096;
+
+/// Moves the bytes after the first `n` to the front, dropping the first `n`.
+fn consume_front<R: RefCnt, U, L: LenCap, G: GrowthPolicy>(
+    cowec: &mut CoWec<R, u8, U, L, G>,
+    n: usize,
+) {
+    if n == 0 {
+        return;
+    }
+    if let Some(block) = cowec.make_mut_left() {
+        unsafe {
+            let len = (*block).len();
+            let n = n.min(len);
+            let data = CoWecBlock::get_data_mut(block);
+            ptr::copy(data.add(n), data, len - n);
+            (*block).set_len(len - n);
+        }
+    }
+}
+
+fn wrong_variant() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "CoWec not holding the left variant",
+    )
+}
+
+/// Appends the bytes to the left variant.
+///
+/// Writes as much as fits into the block (reporting 0 written once it is full). Writing into
+/// other variants is an error.
+impl<R, U, L, G> io::Write for CoWec<R, u8, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.is_left() {
+            return Err(wrong_variant());
+        }
+        // Don't copy a shared block just to append nothing
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let block = self.make_mut_left().ok_or_else(wrong_variant)?;
+        unsafe {
+            let len = (*block).len();
+            let n = buf.len().min(CoWecBlock::<R, u8, L>::LEN_MASK - len);
+            let block = CoWecBlock::reserve::<G>(block, n);
+            self.ptr = block as usize;
+            let data = CoWecBlock::get_data_mut(block).cast::<u8>();
+            ptr::copy_nonoverlapping(buf.as_ptr(), data.add(len), n);
+            (*block).set_len(len + n);
+            Ok(n)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Consumes the bytes from the front of the left variant.
+///
+/// There's no room for a read cursor in a `CoWec`, therefore the read bytes are removed and the
+/// rest is moved to the front. This makes it work as a simple FIFO together with
+/// [`Write`][io::Write], but each read costs `O(len)`. Reading from other variants is an error.
+impl<R, U, L, G> io::Read for CoWec<R, u8, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.left_slice().ok_or_else(wrong_variant)?;
+        let n = buf.len().min(bytes.len());
+        buf[..n].copy_from_slice(&bytes[..n]);
+        consume_front(self, n);
+        Ok(n)
+    }
+}
+
+/// The buffer is the whole left variant, [`consume`][io::BufRead::consume] removes from its front.
+///
+/// See the [`Read`][io::Read] implementation.
+impl<R, U, L, G> io::BufRead for CoWec<R, u8, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.left_slice().ok_or_else(wrong_variant)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        consume_front(self, amt);
+    }
+}
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Writes the left variant as the number of elements followed by their raw bytes.
+    ///
+    /// The number is a 4-byte little-endian integer, the bytes are in the native byte order of
+    /// the elements (so the reading side needs to use the same one).
+    ///
+    /// # Errors
+    ///
+    /// If this is not the left variant or if the writer fails.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    pub unsafe fn send_left<W: io::Write>(&self, writer: &mut W) -> io::Result<()>
+    where
+        T: Copy,
+    {
+        let len = self.len_left().ok_or_else(wrong_variant)?;
+        let len = u32::try_from(len).expect("Block length always fits into u32");
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(self.left_bytes())
+    }
+
+    /// Reads a left `CoWec` written by [`send_left`][CoWec::send_left].
+    ///
+    /// # Errors
+    ///
+    /// If the reader fails (including [`UnexpectedEof`][io::ErrorKind::UnexpectedEof] when the
+    /// data ends early) or if the length is over the maximum of a block
+    /// ([`InvalidData`][io::ErrorKind::InvalidData]).
+    ///
+    /// # Safety
+    ///
+    /// Any bit pattern must be a valid `T` (or at least the ones that are read must be).
+    pub unsafe fn recv_left<Rd: io::Read>(reader: &mut Rd) -> io::Result<Self>
+    where
+        T: Copy,
+    {
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let len = u32::from_le_bytes(len) as usize;
+        let limit = CoWecBlock::<R, T, L>::LEN_MASK;
+        if len > limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                CapacityError {
+                    requested: len,
+                    limit,
+                },
+            ));
+        }
+        let elem = mem::size_of::<T>();
+        // The length comes from the other side and can't be trusted. Read a bounded chunk at a
+        // time and grow only as it arrives, so a short stream fails before allocating it all.
+        let chunk = (RECV_CHUNK / elem.max(1)).max(1);
+        let mut scratch = vec![0; chunk.min(len) * elem];
+        let mut me = Self::new_left();
+        while me.len_left() != Some(len) {
+            let done = me.len_left().expect("Created as left");
+            let n = chunk.min(len - done);
+            let bytes = &mut scratch[..n * elem];
+            reader.read_exact(bytes)?;
+            me.reserve_left_by::<ExactGrowth>(n);
+            let block = me.make_mut_left().expect("Created as left");
+            let data = CoWecBlock::get_data_mut(block).add(done).cast::<u8>();
+            ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+            (*block).set_len(done + n);
+        }
+        Ok(me)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, ErrorKind, Read, Write};
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::{LargeCoWec, RCell};
+
+    type C = CoWec<RCell, u8, ()>;
+
+    #[test]
+    fn write_read() {
+        let mut c = C::new_left();
+        let shared = c.clone();
+        c.write_all(b"Hello ").unwrap();
+        let name = "World";
+        write!(c, "{}\nSecond", name).unwrap();
+        assert!(shared.left_slice().unwrap().is_empty());
+
+        let mut line = String::new();
+        c.read_line(&mut line).unwrap();
+        assert_eq!(line, "Hello World\n");
+        let mut buf = [0; 3];
+        c.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"Sec");
+        assert_eq!(c.fill_buf().unwrap(), b"ond");
+        let mut rest = Vec::new();
+        c.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"ond");
+        assert_eq!(c.read(&mut buf).unwrap(), 0);
+
+        let mut r = C::new_right();
+        assert!(r.write(b"x").is_err());
+        assert!(r.write(b"").is_err());
+        assert!(r.read(&mut buf).is_err());
+    }
+
+    #[test]
+    fn write_empty() {
+        let mut c = C::from(&b"abc"[..]);
+        let shared = c.clone();
+        assert_eq!(c.write(b"").unwrap(), 0);
+        // Still sharing the block, no copy was made
+        assert!(C::ptr_eq(&c, &shared));
+    }
+
+    #[test]
+    fn write_full() {
+        let mut c = C::new_left();
+        assert_eq!(c.write(&[0; 5000]).unwrap(), 4095);
+        assert_eq!(c.write(&[0]).unwrap(), 0);
+        assert_eq!(c.write_all(&[0]).unwrap_err().kind(), ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn send() {
+        let mut out = Vec::new();
+        let c = CoWec::<RCell, u16, ()>::from(&[1, 0x0302][..]);
+        unsafe { c.send_left(&mut out) }.unwrap();
+        let mut expected = vec![2, 0, 0, 0];
+        expected.extend_from_slice(&1u16.to_ne_bytes());
+        expected.extend_from_slice(&0x0302u16.to_ne_bytes());
+        assert_eq!(out, expected);
+
+        let mut out = Vec::new();
+        let big = LargeCoWec::<RCell, u8, ()>::from(vec![7; 0x1_0203]);
+        unsafe { big.send_left(&mut out) }.unwrap();
+        assert_eq!(&out[..4], &[3, 2, 1, 0]);
+        assert_eq!(out.len(), 4 + 0x1_0203);
+
+        let err = unsafe { C::new_right().send_left(&mut Vec::new()) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let mut full = [0; 3];
+        let err = unsafe { C::from(&[1][..]).send_left(&mut &mut full[..]) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn recv() {
+        type W = CoWec<RCell, u16, ()>;
+        let mut out = Vec::new();
+        unsafe {
+            W::from(&[1, 0xffff, 3][..]).send_left(&mut out).unwrap();
+            W::new_left().send_left(&mut out).unwrap();
+        }
+        let mut input = &out[..];
+        let c = unsafe { W::recv_left(&mut input) }.unwrap();
+        assert_eq!(c.left_slice(), Some(&[1, 0xffff, 3][..]));
+        let c = unsafe { W::recv_left(&mut input) }.unwrap();
+        assert_eq!(c.left_slice(), Some(&[][..]));
+        assert!(input.is_empty());
+
+        let err = unsafe { W::recv_left(&mut &out[..7]) }.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        let err = unsafe { W::recv_left(&mut &[1, 0][..]) }.err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        let err = unsafe { W::recv_left(&mut &[0, 0x10, 0, 0][..]) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        // The header claims much more than there is, fails without allocating all of it.
+        let big = [0xff, 0xff, 0xff, 0x07, 1, 2, 3];
+        let err = unsafe { LargeCoWec::<RCell, u8, ()>::recv_left(&mut &big[..]) }
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        // Longer than a chunk
+        let long = (0..3000).map(|i| i as u16).collect::<Vec<_>>();
+        let mut out = Vec::new();
+        unsafe { W::from(&long[..]).send_left(&mut out) }.unwrap();
+        let c = unsafe { W::recv_left(&mut &out[..]) }.unwrap();
+        assert_eq!(c.left_slice(), Some(&long[..]));
+    }
+}
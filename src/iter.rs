@@ -0,0 +1,281 @@
+//! Iterators over the elements.
+
+use alloc::vec;
+use core::iter::{FusedIterator, Zip};
+use core::marker::PhantomData;
+use core::slice;
+
+use super::{CoWec, LenCap, RefCnt};
+
+/// Generates a wrapper around one of the slice iterators, tied to the borrowed [`CoWec`].
+macro_rules! slice_iter {
+    ($(#[$meta:meta])* $name:ident, $inner:ty, $item:ty, $borrow:ty) => {
+        $(#[$meta])*
+        pub struct $name<'a, R: RefCnt, T, U, L: LenCap = u16> {
+            inner: $inner,
+            _cowec: PhantomData<$borrow>,
+        }
+
+        impl<'a, R: RefCnt, T, U, L: LenCap> $name<'a, R, T, U, L> {
+            pub(crate) fn new(inner: $inner) -> Self {
+                Self {
+                    inner,
+                    _cowec: PhantomData,
+                }
+            }
+        }
+
+        impl<'a, R: RefCnt, T, U, L: LenCap> Iterator for $name<'a, R, T, U, L> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<$item> {
+                self.inner.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<'a, R: RefCnt, T, U, L: LenCap> DoubleEndedIterator for $name<'a, R, T, U, L> {
+            fn next_back(&mut self) -> Option<$item> {
+                self.inner.next_back()
+            }
+        }
+
+        impl<'a, R: RefCnt, T, U, L: LenCap> ExactSizeIterator for $name<'a, R, T, U, L> {}
+
+        impl<'a, R: RefCnt, T, U, L: LenCap> FusedIterator for $name<'a, R, T, U, L> {}
+    };
+}
+
+slice_iter!(
+    /// Iterator over overlapping windows of the left variant.
+    ///
+    /// Created by [`CoWec::windows_left`].
+    WindowsLeft,
+    slice::Windows<'a, T>,
+    &'a [T],
+    &'a CoWec<R, T, U, L>
+);
+
+slice_iter!(
+    /// Iterator over non-overlapping chunks of the left variant.
+    ///
+    /// Created by [`CoWec::chunks_left`].
+    ChunksLeft,
+    slice::Chunks<'a, T>,
+    &'a [T],
+    &'a CoWec<R, T, U, L>
+);
+
+slice_iter!(
+    /// Iterator over non-overlapping mutable chunks of the left variant.
+    ///
+    /// Created by [`CoWec::chunks_left_mut`].
+    ChunksLeftMut,
+    slice::ChunksMut<'a, T>,
+    &'a mut [T],
+    &'a mut CoWec<R, T, U, L>
+);
+
+slice_iter!(
+    /// Iterator over overlapping windows of the right variant.
+    ///
+    /// Created by [`CoWec::windows_right`].
+    WindowsRight,
+    slice::Windows<'a, U>,
+    &'a [U],
+    &'a CoWec<R, T, U, L>
+);
+
+slice_iter!(
+    /// Iterator over non-overlapping chunks of the right variant.
+    ///
+    /// Created by [`CoWec::chunks_right`].
+    ChunksRight,
+    slice::Chunks<'a, U>,
+    &'a [U],
+    &'a CoWec<R, T, U, L>
+);
+
+slice_iter!(
+    /// Iterator over non-overlapping mutable chunks of the right variant.
+    ///
+    /// Created by [`CoWec::chunks_right_mut`].
+    ChunksRightMut,
+    slice::ChunksMut<'a, U>,
+    &'a mut [U],
+    &'a mut CoWec<R, T, U, L>
+);
+
+/// Iterator over the elements removed by [`CoWec::splice_left`].
+///
+/// Unlike with [`Vec::splice`][alloc::vec::Vec::splice], the splicing is already done by the time
+/// this is returned, dropping it early doesn't change the result.
+pub struct SpliceLeft<T> {
+    inner: vec::IntoIter<T>,
+}
+
+impl<T> SpliceLeft<T> {
+    pub(crate) fn new(inner: vec::IntoIter<T>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Iterator for SpliceLeft<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for SpliceLeft<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.inner.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for SpliceLeft<T> {}
+
+impl<T> FusedIterator for SpliceLeft<T> {}
+
+/// Iterator over pairs of left and right elements of two [`CoWec`]s.
+///
+/// Created by [`CoWec::zip_left_right`].
+pub struct ZipLeftRight<'a, T, U> {
+    inner: Zip<slice::Iter<'a, T>, slice::Iter<'a, U>>,
+}
+
+impl<'a, T, U> ZipLeftRight<'a, T, U> {
+    pub(crate) fn new(inner: Zip<slice::Iter<'a, T>, slice::Iter<'a, U>>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<'a, T, U> Iterator for ZipLeftRight<'a, T, U> {
+    type Item = (&'a T, &'a U);
+
+    fn next(&mut self) -> Option<(&'a T, &'a U)> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, T, U> DoubleEndedIterator for ZipLeftRight<'a, T, U> {
+    fn next_back(&mut self) -> Option<(&'a T, &'a U)> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, T, U> ExactSizeIterator for ZipLeftRight<'a, T, U> {}
+
+impl<'a, T, U> FusedIterator for ZipLeftRight<'a, T, U> {}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+
+    use crate::{CoWecBlock, RCell};
+
+    type C = crate::CoWec<RCell, u32, u32>;
+
+    fn left(n: u32) -> C {
+        C::from((0..n).collect::<Vec<_>>())
+    }
+
+    fn right(n: u32) -> C {
+        let block = unsafe { CoWecBlock::create_for(n as usize) };
+        for i in 0..n {
+            unsafe { CoWecBlock::insert(block, i as usize, i) };
+        }
+        C::from_right_block(block)
+    }
+
+    #[test]
+    fn windows() {
+        let v = (0..5).collect::<Vec<u32>>();
+        for size in 1..7 {
+            let expected = v.windows(size).collect::<Vec<_>>();
+            assert_eq!(left(5).windows_left(size).collect::<Vec<_>>(), expected);
+            assert_eq!(right(5).windows_right(size).collect::<Vec<_>>(), expected);
+            assert_eq!(left(5).windows_left(size).len(), expected.len());
+            assert_eq!(left(5).windows_right(size).count(), 0);
+            assert_eq!(right(5).windows_left(size).count(), 0);
+        }
+    }
+
+    #[test]
+    fn chunks() {
+        let v = (0..5).collect::<Vec<u32>>();
+        for size in 1..7 {
+            let expected = v.chunks(size).collect::<Vec<_>>();
+            assert_eq!(left(5).chunks_left(size).collect::<Vec<_>>(), expected);
+            assert_eq!(right(5).chunks_right(size).collect::<Vec<_>>(), expected);
+            assert_eq!(
+                left(5).chunks_left(size).rev().collect::<Vec<_>>(),
+                v.chunks(size).rev().collect::<Vec<_>>()
+            );
+            assert_eq!(C::new_stub().chunks_left(size).count(), 0);
+        }
+    }
+
+    #[test]
+    fn chunks_mut() {
+        let mut c = left(5);
+        let d = c.clone();
+        let mut iter = c.chunks_left_mut(2);
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.len(), 2);
+        for (i, chunk) in c.chunks_left_mut(2).enumerate() {
+            for elem in chunk {
+                *elem += i as u32 * 10;
+            }
+        }
+        assert_eq!(Vec::from(c), vec![0, 1, 12, 13, 24]);
+        assert_eq!(Vec::from(d), vec![0, 1, 2, 3, 4]);
+
+        let mut c = right(3);
+        for chunk in c.chunks_right_mut(1) {
+            chunk[0] *= 2;
+        }
+        assert_eq!(c.chunks_right(3).next().unwrap(), &[0, 2, 4]);
+        assert_eq!(C::new_stub().chunks_right_mut(1).count(), 0);
+    }
+
+    #[test]
+    fn zip_left_right() {
+        let keys = left(4);
+        let values = right(3);
+        let zipped = C::zip_left_right(&keys, &values).collect::<Vec<_>>();
+        assert_eq!(zipped, vec![(&0, &0), (&1, &1), (&2, &2)]);
+        assert_eq!(C::zip_left_right(&keys, &values).len(), 3);
+        assert_eq!(
+            C::zip_left_right(&keys, &values).next_back(),
+            Some((&2, &2))
+        );
+        assert_eq!(C::zip_left_right(&keys, &keys).count(), 0);
+        assert_eq!(C::zip_left_right(&values, &values).count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn windows_zero() {
+        left(3).windows_left(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn chunks_zero() {
+        C::new_stub().chunks_left(0);
+    }
+}
@@ -0,0 +1,187 @@
+//! A `CoWec` known to hold the left variant.
+
+use core::borrow::{Borrow, BorrowMut};
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::hash::{Hash, Hasher};
+use core::ops::{Deref, DerefMut};
+
+use super::{CoWec, DoublingGrowth, GrowthPolicy, LenCap, RefCnt};
+
+/// A [`CoWec`] that always holds the left variant.
+///
+/// As the variant is known, it can act as a slice of `T` through [`Deref`] and [`Borrow`] (which
+/// allows looking it up by a `&[T]` in a map). The comparisons and hashing are the ones of the
+/// slice.
+///
+/// Created through [`TryFrom`], which hands back the original if it holds another variant.
+pub struct LeftCoWec<R: RefCnt, T, U, L: LenCap = u16, G: GrowthPolicy = DoublingGrowth> {
+    cowec: CoWec<R, T, U, L, G>,
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> LeftCoWec<R, T, U, L, G> {
+    /// Creates a new empty one.
+    pub fn new() -> Self {
+        Self {
+            cowec: CoWec::new_left(),
+        }
+    }
+
+    /// Unwraps the `CoWec`.
+    pub fn into_inner(self) -> CoWec<R, T, U, L, G> {
+        self.cowec
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Default for LeftCoWec<R, T, U, L, G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> TryFrom<CoWec<R, T, U, L, G>>
+    for LeftCoWec<R, T, U, L, G>
+{
+    type Error = CoWec<R, T, U, L, G>;
+
+    fn try_from(cowec: CoWec<R, T, U, L, G>) -> Result<Self, CoWec<R, T, U, L, G>> {
+        if cowec.is_left() {
+            Ok(Self { cowec })
+        } else {
+            Err(cowec)
+        }
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> From<LeftCoWec<R, T, U, L, G>>
+    for CoWec<R, T, U, L, G>
+{
+    fn from(left: LeftCoWec<R, T, U, L, G>) -> Self {
+        left.cowec
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Clone for LeftCoWec<R, T, U, L, G> {
+    fn clone(&self) -> Self {
+        Self {
+            cowec: self.cowec.clone(),
+        }
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Deref for LeftCoWec<R, T, U, L, G> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.cowec
+            .left_slice()
+            .expect("LeftCoWec always holds the left variant")
+    }
+}
+
+/// Copies the block first if it is shared.
+impl<R: RefCnt, T: Clone, U, L: LenCap, G: GrowthPolicy> DerefMut for LeftCoWec<R, T, U, L, G> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.cowec
+            .left_slice_mut()
+            .expect("LeftCoWec always holds the left variant")
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Borrow<[T]> for LeftCoWec<R, T, U, L, G> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<R: RefCnt, T: Clone, U, L: LenCap, G: GrowthPolicy> BorrowMut<[T]>
+    for LeftCoWec<R, T, U, L, G>
+{
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> AsRef<[T]> for LeftCoWec<R, T, U, L, G> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<R: RefCnt, T: PartialEq, U, L: LenCap, G: GrowthPolicy> PartialEq
+    for LeftCoWec<R, T, U, L, G>
+{
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<R: RefCnt, T: Eq, U, L: LenCap, G: GrowthPolicy> Eq for LeftCoWec<R, T, U, L, G> {}
+
+impl<R: RefCnt, T: PartialOrd, U, L: LenCap, G: GrowthPolicy> PartialOrd
+    for LeftCoWec<R, T, U, L, G>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<R: RefCnt, T: Ord, U, L: LenCap, G: GrowthPolicy> Ord for LeftCoWec<R, T, U, L, G> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<R: RefCnt, T: Hash, U, L: LenCap, G: GrowthPolicy> Hash for LeftCoWec<R, T, U, L, G> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::prelude::v1::*;
+
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, u32, String>;
+    type LC = LeftCoWec<RCell, u32, String>;
+
+    fn left(elems: &[u32]) -> LC {
+        LC::try_from(C::from(elems)).ok().unwrap()
+    }
+
+    #[test]
+    fn try_from() {
+        let l = left(&[1, 2]);
+        assert_eq!(&*l, &[1, 2]);
+        assert!(LC::try_from(C::new_right()).err().unwrap().is_right());
+        assert!(LC::try_from(C::new_stub()).err().unwrap().is_stub());
+        assert!(C::from(l).is_left());
+        assert!(LC::default().is_empty());
+    }
+
+    #[test]
+    fn borrow() {
+        let mut set = HashSet::new();
+        set.insert(left(&[1, 2]));
+        set.insert(LC::new());
+        assert!(set.contains(&[1, 2][..]));
+        assert!(set.contains(&[][..]));
+        assert!(!set.contains(&[2][..]));
+        assert!(LC::new() < left(&[0]));
+    }
+
+    #[test]
+    fn deref_mut() {
+        let mut l = left(&[1, 2]);
+        let shared = l.clone();
+        l[0] = 10;
+        BorrowMut::<[u32]>::borrow_mut(&mut l)[1] = 20;
+        assert_eq!(&*l, &[10, 20]);
+        assert_eq!(&*shared, &[1, 2]);
+        assert_eq!(l.into_inner().left_slice(), Some(&[10, 20][..]));
+    }
+}
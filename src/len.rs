@@ -0,0 +1,57 @@
+//! Encodings of the length and capacity of a block.
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The integer type holding both the length and the capacity of a block.
+///
+/// The lower [`LEN_BITS`][LenCap::LEN_BITS] bits hold the length, the rest (above them) the
+/// capacity. Implemented for:
+///
+/// * `u16`: the default. The length has 12 bits, therefore a block holds at most 4095 elements.
+/// * `u32`: for large blocks, up to 2^27 - 1 elements (see [`LargeCoWec`][crate::LargeCoWec]).
+///
+/// This trait is sealed, it can't be implemented outside of this crate.
+pub trait LenCap: Copy + sealed::Sealed {
+    /// Number of the bits holding the length.
+    const LEN_BITS: u32;
+
+    /// Converts the raw (encoded) value to `usize`.
+    fn to_usize(self) -> usize;
+
+    /// Converts back from `usize`.
+    ///
+    /// The value is expected to fit.
+    fn from_usize(raw: usize) -> Self;
+}
+
+impl sealed::Sealed for u16 {}
+
+impl LenCap for u16 {
+    const LEN_BITS: u32 = 12;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+
+    fn from_usize(raw: usize) -> Self {
+        debug_assert!(raw <= u16::MAX as usize);
+        raw as u16
+    }
+}
+
+impl sealed::Sealed for u32 {}
+
+impl LenCap for u32 {
+    const LEN_BITS: u32 = 27;
+
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+
+    fn from_usize(raw: usize) -> Self {
+        debug_assert!(raw <= u32::MAX as usize);
+        raw as u32
+    }
+}
@@ -1,9 +1,109 @@
-use std::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
-use std::cell::Cell;
-use std::marker::PhantomData;
-use std::mem::{self, MaybeUninit};
-use std::ptr;
+#![no_std]
 
+extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+use alloc::alloc::{alloc, dealloc, handle_alloc_error, realloc, Layout};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::convert::Infallible;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Bound, RangeBounds};
+use core::ptr;
+use core::slice;
+use core::sync::atomic::{self, AtomicU16, Ordering as AtomicOrdering};
+
+mod bytes;
+mod checksum;
+mod cowec3;
+mod csv;
+mod cursor;
+mod diff;
+mod dirty;
+mod error;
+mod growth;
+#[cfg(feature = "std")]
+mod io;
+mod iter;
+mod left;
+mod len;
+mod listener;
+#[cfg(any(test, feature = "fuzzing"))]
+#[doc(hidden)]
+pub mod model;
+mod pool;
+mod sliced;
+mod snapshot;
+mod stats;
+
+pub use cowec3::CoWec3;
+pub use cursor::{CoWecCursorLeft, CoWecCursorLeftMut};
+pub use diff::Diff;
+pub use dirty::DirtyCoWec;
+pub use error::{
+    Base64Error, CapacityError, DiffError, HexError, InsertError, ParseError, SeekError,
+    TryReserveError,
+};
+pub use growth::{DoublingGrowth, ExactGrowth, GrowthPolicy, LinearGrowth};
+pub use iter::{
+    ChunksLeft, ChunksLeftMut, ChunksRight, ChunksRightMut, SpliceLeft, WindowsLeft, WindowsRight,
+    ZipLeftRight,
+};
+pub use left::LeftCoWec;
+pub use len::LenCap;
+pub use listener::ChangeListener;
+pub use pool::BlockPool;
+pub use sliced::SlicedBlock;
+pub use snapshot::CoWecSnapshot;
+pub use stats::MemoryStats;
+
+/// Turns the range into `start..end`, checking it fits into `len`.
+///
+/// Panics with the same messages as slice indexing.
+fn resolve_range<B: RangeBounds<usize>>(range: B, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "slice index starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end index {} out of range for slice of length {}",
+        end,
+        len
+    );
+    (start, end)
+}
+
+/// A reference count stored inside the shared block.
+///
+/// # Safety
+///
+/// The implementation must count correctly. If [`dec_ref`][RefCnt::dec_ref] reports the count
+/// dropped to zero while there are still other handles around, or [`is_unique`][RefCnt::is_unique]
+/// claims uniqueness when the block is shared, the block is freed or mutated under someone's
+/// hands.
 pub unsafe trait RefCnt: Default {
     /// Decrement the reference count.
     ///
@@ -14,10 +114,22 @@ pub unsafe trait RefCnt: Default {
     ///
     /// If it is too high, false is returned and the state is left unchanged.
     fn inc_ref(&self) -> bool;
+
+    /// Is there only one reference to the block?
+    ///
+    /// If so, the block may be modified in place.
+    fn is_unique(&self) -> bool;
+
+    /// The current number of references.
+    ///
+    /// With a thread-safe implementation, this is only a snapshot that may already be out of date
+    /// by the time it is returned. Meant for diagnostics.
+    fn count(&self) -> u16;
 }
 
+/// A single-threaded reference count (the [`Rc`][alloc::rc::Rc] equivalent).
 #[repr(transparent)]
-struct RCell(Cell<u16>);
+pub struct RCell(Cell<u16>);
 
 impl Default for RCell {
     fn default() -> Self {
@@ -42,6 +154,55 @@ unsafe impl RefCnt for RCell {
             true
         }
     }
+
+    fn is_unique(&self) -> bool {
+        self.0.get() == 1
+    }
+
+    fn count(&self) -> u16 {
+        self.0.get()
+    }
+}
+
+/// A thread-safe reference count (the [`Arc`][alloc::sync::Arc] equivalent).
+#[repr(transparent)]
+pub struct AtomicRCell(AtomicU16);
+
+impl Default for AtomicRCell {
+    fn default() -> Self {
+        Self(AtomicU16::new(1))
+    }
+}
+
+unsafe impl RefCnt for AtomicRCell {
+    fn dec_ref(&self) -> bool {
+        // Same as with Arc ‒ release our modifications to whoever destroys the block and acquire
+        // everyone else's if it's us.
+        if self.0.fetch_sub(1, AtomicOrdering::Release) == 1 {
+            atomic::fence(AtomicOrdering::Acquire);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn inc_ref(&self) -> bool {
+        // We already hold a reference, so no synchronization is needed to create another one.
+        self.0
+            .fetch_update(AtomicOrdering::Relaxed, AtomicOrdering::Relaxed, |old| {
+                old.checked_add(1)
+            })
+            .is_ok()
+    }
+
+    fn is_unique(&self) -> bool {
+        // Acquire, to see all the modifications made through the other (now gone) references.
+        self.0.load(AtomicOrdering::Acquire) == 1
+    }
+
+    fn count(&self) -> u16 {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
 }
 
 /// The header of block of CoWec.
@@ -51,23 +212,29 @@ unsafe impl RefCnt for RCell {
 /// The `len` field encodes both the current active length and the capacity. It can't be created or
 /// destroyed directly, as it needs direct talking to a memory allocator.
 ///
-/// repr(C) so we can control the layout. The align(2) is to make sure that we can abuse the last
-/// bit of the pointer for a tag to denote an enum of one or other T.
-#[repr(C, align(2))]
-struct CoWecBlock<R, T> {
+/// The type of the `len` field decides how many elements the block can hold, see [`LenCap`].
+///
+/// repr(C) so we can control the layout. The align(4) is to make sure that we can abuse the last
+/// two bits of the pointer for a tag to denote an enum of one or other T ([`CoWec`] uses one of
+/// them, [`CoWec3`] both). It doesn't cost anything, the header has 4 bytes anyway.
+#[repr(C, align(4))]
+struct CoWecBlock<R, T, L = u16> {
     /// Reference count, of some implementation.
     ///
     /// We can choose if we are thread safe or not by this (eg. equivalent to Rc vs Arc).
     rcell: R,
     /// The length & capacity.
     ///
-    /// The upper 4 bits are the capacity. If the capacity is set to 0, it is „tight“ ‒ exactly the
-    /// same number of slots as they are needed. Other value `i` denotes that there are `2^i`
+    /// The upper bits are the capacity (4 of them with `u16`, 5 with `u32`). If the capacity is set
+    /// to 0, it is „tight“ ‒ the block was shrunk to a capacity that is not a power of two (usually
+    /// the number of elements it held at that time). The actual capacity of a tight block doesn't
+    /// fit here, it is stored in a `usize` right in front of the header (see
+    /// [`TIGHT_OFFSET`][CoWecBlock::TIGHT_OFFSET]). Other value `i` denotes that there are `2^i`
     /// slots. The idea is that once we start sharing the block, it can't change any more and we
     /// can shrink it, but until then we use the classical doubling strategy.
     ///
-    /// The rest 12 bits denote the used length.
-    len: u16,
+    /// The rest ([`LenCap::LEN_BITS`], 12 with `u16`) denote the used length.
+    len: L,
 
     /// The actual payload.
     ///
@@ -76,33 +243,82 @@ struct CoWecBlock<R, T> {
     data: [MaybeUninit<T>; 0],
 }
 
-impl<R: RefCnt, T> CoWecBlock<R, T> {
-    const LEN_MASK: u16 = 0b0000_1111_1111_1111;
-    const CAP_OFFSET: u16 = 12;
-    const DATA_OFFSET: usize = Layout::new::<Self>().size();
+impl<R: RefCnt, T, L: LenCap> CoWecBlock<R, T, L> {
+    const LEN_MASK: usize = (1 << L::LEN_BITS) - 1;
+    const CAP_OFFSET: u32 = L::LEN_BITS;
+    const DATA_OFFSET: usize = mem::offset_of!(Self, data);
+    /// The data array must be at the very end of the header, so the header can be extended by the
+    /// array of the actual elements without any padding in between.
+    ///
+    /// That holds with repr(C), as the array has size 0 and the struct is aligned at least as much
+    /// as the array, but better check at compile time.
+    const DATA_AT_END: () = assert!(
+        Self::DATA_OFFSET == mem::size_of::<Self>(),
+        "Data array is not at the end of the header"
+    );
+    /// The tagged pointers need the two lowest bits of the block address to be zero.
+    ///
+    /// Higher alignment of `T` only raises the alignment of the block, so this always holds, but
+    /// better make sure.
+    const TAG_BITS_FREE: () = assert!(mem::align_of::<Self>() >= 4, "Block alignment too low");
+    /// Where the header starts in the allocation of a tight block.
+    ///
+    /// The capacity is stored in front of it, padded so the header stays aligned. As the offset is
+    /// a multiple of the `usize` size too, the capacity ends right at the header.
+    const TIGHT_OFFSET: usize = mem::size_of::<usize>().next_multiple_of(mem::align_of::<Self>());
 
     fn len(&self) -> usize {
-        (self.len & Self::LEN_MASK) as usize
+        self.len.to_usize() & Self::LEN_MASK
     }
 
-    fn capacity(&self) -> usize {
-        let cap = (self.len >> Self::CAP_OFFSET) as u32;
-        if cap == 0 {
-            self.len()
+    /// The number of slots of the block.
+    ///
+    /// This takes a raw pointer, not a reference, as the capacity of a tight block is stored in
+    /// front of the header, out of reach of a reference to it.
+    unsafe fn capacity(me: *const Self) -> usize {
+        if Self::is_tight(me) {
+            Self::tight_capacity(me)
         } else {
-            2usize.pow(cap)
+            2usize.pow(((*me).len.to_usize() >> Self::CAP_OFFSET) as u32)
         }
     }
 
+    /// The capacity stored in front of the header of a tight block.
+    unsafe fn tight_capacity(me: *const Self) -> usize {
+        me.cast::<usize>().sub(1).read()
+    }
+
+    /// Is the block tight (has a capacity that can't be encoded in the header)?
+    unsafe fn is_tight(me: *const Self) -> bool {
+        (*me).len.to_usize() >> Self::CAP_OFFSET == 0
+    }
+
     fn layout(capacity: usize) -> Layout {
         let head = Layout::new::<Self>();
         let tail = Layout::array::<MaybeUninit<T>>(capacity).expect("Invalid array layout");
-        let layout = head.extend(tail).expect("Invalid layout created");
-        // That should not happen as the data array has size 0 and must be correctly aligned
-        // already, so there's no reason why further elements should need any padding for
-        // alignment.
-        assert_eq!(layout.1, Self::DATA_OFFSET, "Offset mismatch");
-        layout.0
+        // Thanks to this, the tail starts right at the DATA_OFFSET.
+        let () = Self::DATA_AT_END;
+        let (layout, _) = head.extend(tail).expect("Invalid layout created");
+        layout
+    }
+
+    /// The layout of a tight block, with the capacity in front of the header.
+    fn tight_layout(capacity: usize) -> Layout {
+        let (layout, offset) = Layout::new::<usize>()
+            .extend(Self::layout(capacity))
+            .expect("Invalid layout created");
+        debug_assert_eq!(offset, Self::TIGHT_OFFSET);
+        layout
+    }
+
+    /// The start and the layout of the whole allocation holding the block.
+    unsafe fn allocation(me: *mut Self) -> (*mut u8, Layout) {
+        if Self::is_tight(me) {
+            let layout = Self::tight_layout(Self::tight_capacity(me));
+            (me.cast::<u8>().sub(Self::TIGHT_OFFSET), layout)
+        } else {
+            (me.cast(), Self::layout(Self::capacity(me)))
+        }
     }
 
     unsafe fn get_data_mut(me: *mut Self) -> *mut MaybeUninit<T> {
@@ -113,21 +329,45 @@ impl<R: RefCnt, T> CoWecBlock<R, T> {
         me.cast::<u8>().add(Self::DATA_OFFSET).cast()
     }
 
-    /// A very much manual destructor. We can't really do proper Drop due to talking to the
-    /// allocator.
-    unsafe fn dispose(me: *mut Self) {
+    /// The initialized elements as a slice.
+    ///
+    /// The caller chooses the lifetime and must make sure the block outlives it and nobody
+    /// modifies it meanwhile.
+    unsafe fn as_slice<'a>(me: *const Self) -> &'a [T] {
+        slice::from_raw_parts(Self::get_data(me).cast(), (*me).len())
+    }
+
+    /// The initialized elements as a mutable slice.
+    ///
+    /// Like [`as_slice`][CoWecBlock::as_slice], and the block must not be shared (or accessed
+    /// through any other reference meanwhile).
+    unsafe fn as_slice_mut<'a>(me: *mut Self) -> &'a mut [T] {
+        slice::from_raw_parts_mut(Self::get_data_mut(me).cast(), (*me).len())
+    }
+
+    /// Drops all the elements, leaving the block empty (but keeping the capacity).
+    unsafe fn clear(me: *mut Self) {
         let data = Self::get_data_mut(me);
-        let me_ref = me.as_mut().expect("Got invalid pointer to dispose");
-        let layout = Self::layout(me_ref.capacity());
-        ptr::drop_in_place(&mut me_ref.rcell);
+        let me_ref = me.as_mut().expect("Got invalid pointer to clear");
+        let len = me_ref.len();
+        // Set the length first, so a panicking destructor leaks instead of double-dropping.
+        me_ref.set_len(0);
         if mem::needs_drop::<T>() {
-            let len = me_ref.len();
             for i in 0..len {
                 let elem: &mut MaybeUninit<_> = &mut *data.add(i);
                 ptr::drop_in_place(elem.as_mut_ptr()); // Drop the thing *inside* the MaybeUninit
             }
         }
-        dealloc(me.cast(), layout);
+    }
+
+    /// A very much manual destructor. We can't really do proper Drop due to talking to the
+    /// allocator.
+    unsafe fn dispose(me: *mut Self) {
+        let me_ref = me.as_mut().expect("Got invalid pointer to dispose");
+        let (start, layout) = Self::allocation(me);
+        ptr::drop_in_place(&mut me_ref.rcell);
+        Self::clear(me);
+        dealloc(start, layout);
     }
 
     unsafe fn dec_ref(me: *mut Self) {
@@ -147,58 +387,311 @@ impl<R: RefCnt, T> CoWecBlock<R, T> {
     }
 
     unsafe fn create(capacity: usize) -> *mut Self {
+        let () = Self::TAG_BITS_FREE;
         debug_assert!(capacity.is_power_of_two());
+        // Capacity of 1 would be encoded as 0, which means tight (with the capacity stored out of
+        // the header, which this one doesn't have).
+        assert!(capacity >= 2, "Capacity {} can't be encoded", capacity);
         // TODO: Range check?
-        let cap_encoded = capacity.trailing_zeros() as u16;
+        let cap_encoded = capacity.trailing_zeros() as usize;
         let layout = Self::layout(capacity);
         let header = Self {
             rcell: R::default(),
-            len: cap_encoded << Self::CAP_OFFSET,
+            len: L::from_usize(cap_encoded << Self::CAP_OFFSET),
             data: [],
         };
-        debug_assert_eq!(header.capacity(), capacity);
-        debug_assert_eq!(header.len(), 0);
         let me = alloc(layout).cast::<Self>();
         if me.is_null() {
             handle_alloc_error(layout);
         }
         ptr::write(me, header);
+        debug_assert_eq!(Self::capacity(me), capacity);
+        debug_assert_eq!((*me).len(), 0);
 
         me
     }
 
+    /// Creates an empty block with room for at least `len` elements.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is over the maximum length of a block.
+    unsafe fn create_for(len: usize) -> *mut Self {
+        assert!(
+            len <= Self::LEN_MASK,
+            "Length {} over the limit of {}",
+            len,
+            Self::LEN_MASK
+        );
+        Self::create(len.next_power_of_two().max(2))
+    }
+
     unsafe fn resize(me: *mut Self, new_cap: usize) -> *mut Self {
+        Self::try_resize(me, new_cap).unwrap_or_else(|layout| handle_alloc_error(layout))
+    }
+
+    /// Like [`resize`][CoWecBlock::resize], but returns the layout it failed to allocate
+    /// instead of aborting.
+    ///
+    /// On failure, the original block is left intact.
+    unsafe fn try_resize(me: *mut Self, new_cap: usize) -> Result<*mut Self, Layout> {
         debug_assert!(new_cap.is_power_of_two());
         // TODO: Cap range check
-        let cap_encoded = new_cap.trailing_zeros() as u16;
-        let me_ref = me.as_mut().expect("Got invalid pointer to resize");
-        let old_layout = Self::layout(me_ref.capacity());
-        let new_layout = Self::layout(new_cap);
-        let old_len = me_ref.len();
-        let new_me = realloc(me.cast(), old_layout, new_layout.size()).cast::<Self>();
+        let new_me = Self::relocate(me, new_cap, false);
+        if new_me.is_null() {
+            Err(Self::layout(new_cap))
+        } else {
+            Ok(new_me)
+        }
+    }
+
+    /// Moves the block to an allocation with `new_cap` slots, returning null on failure.
+    ///
+    /// A tight block may have any capacity, otherwise it must be a power of two. The length is kept
+    /// (so there must be room for the elements) and, like with `realloc`, the content of all the
+    /// slots that fit into both blocks, not only the elements below the length. On failure, the
+    /// original block is left intact.
+    ///
+    /// The allocator can resize in place only if neither the old nor the new block is tight,
+    /// otherwise the header moves relative to the start of the allocation and the block is
+    /// copied over.
+    unsafe fn relocate(me: *mut Self, new_cap: usize, tight: bool) -> *mut Self {
+        let len = (*me).len();
+        debug_assert!(len <= new_cap);
+        let old_cap = Self::capacity(me);
+        let (old_start, old_layout) = Self::allocation(me);
+        let new_me = if !tight && !Self::is_tight(me) {
+            Self::realloc_to(me, old_layout, Self::layout(new_cap))
+        } else {
+            let (new_layout, offset) = if tight {
+                (Self::tight_layout(new_cap), Self::TIGHT_OFFSET)
+            } else {
+                (Self::layout(new_cap), 0)
+            };
+            let new_start = alloc(new_layout);
+            if new_start.is_null() {
+                return ptr::null_mut();
+            }
+            let new_me = new_start.add(offset).cast::<Self>();
+            // Callers may keep something above the length (eg. splicing), take all that fits
+            let slots = old_cap.min(new_cap);
+            let size = Self::DATA_OFFSET + slots * mem::size_of::<T>();
+            ptr::copy_nonoverlapping(me.cast::<u8>(), new_me.cast::<u8>(), size);
+            dealloc(old_start, old_layout);
+            if tight {
+                new_me.cast::<usize>().sub(1).write(new_cap);
+            }
+            new_me
+        };
+        if new_me.is_null() {
+            return new_me;
+        }
+
+        let cap_encoded = if tight {
+            0
+        } else {
+            new_cap.trailing_zeros() as usize
+        };
+        (*new_me).len = L::from_usize(len | (cap_encoded << Self::CAP_OFFSET));
+        debug_assert_eq!(Self::capacity(new_me), new_cap);
+        debug_assert_eq!((*new_me).len(), len);
+        new_me
+    }
+
+    /// Reallocates the block, returning null on failure.
+    ///
+    /// If the size doesn't change (eg. for zero-sized `T`), the allocator is not bothered at all.
+    unsafe fn realloc_to(me: *mut Self, old_layout: Layout, new_layout: Layout) -> *mut Self {
+        if old_layout.size() == new_layout.size() {
+            me
+        } else {
+            realloc(me.cast(), old_layout, new_layout.size()).cast()
+        }
+    }
+
+    /// The capacity to grow to, so at least `required` elements fit.
+    ///
+    /// The policy suggests it, but it needs to be rounded to a power of two so it can be encoded.
+    unsafe fn grown_capacity<G: GrowthPolicy>(me: *const Self, required: usize) -> usize {
+        G::next_capacity(Self::capacity(me), required)
+            .max(required)
+            .min(Self::LEN_MASK + 1)
+            .next_power_of_two()
+            .max(2)
+    }
+
+    /// Makes sure there's room for at least `additional` more elements, growing the block
+    /// according to the policy if needed.
+    ///
+    /// Returns the (possibly moved) block.
+    unsafe fn reserve<G: GrowthPolicy>(me: *mut Self, additional: usize) -> *mut Self {
+        let me_ref = &*me;
+        let required = me_ref.len() + additional;
+        assert!(
+            required <= Self::LEN_MASK,
+            "Length {} over the limit of {}",
+            required,
+            Self::LEN_MASK
+        );
+        if required <= Self::capacity(me) {
+            me
+        } else {
+            Self::resize(me, Self::grown_capacity::<G>(me, required))
+        }
+    }
+
+    /// Makes sure there's room for at least `additional` more elements, growing the block to
+    /// exactly that if needed.
+    ///
+    /// Unless the result happens to be a power of two, the grown block is tight.
+    ///
+    /// Returns the (possibly moved) block.
+    unsafe fn reserve_exact(me: *mut Self, additional: usize) -> *mut Self {
+        let required = (*me).len() + additional;
+        assert!(
+            required <= Self::LEN_MASK,
+            "Length {} over the limit of {}",
+            required,
+            Self::LEN_MASK
+        );
+        if required <= Self::capacity(me) {
+            return me;
+        }
+        let tight = required < 2 || !required.is_power_of_two();
+        let new_me = Self::relocate(me, required, tight);
         if new_me.is_null() {
-            handle_alloc_error(new_layout);
+            let layout = if tight {
+                Self::tight_layout(required)
+            } else {
+                Self::layout(required)
+            };
+            handle_alloc_error(layout);
+        }
+        new_me
+    }
+
+    /// Moves all the elements of `src` to the end of `dst`, leaving `src` empty.
+    ///
+    /// The elements are moved bitwise, so their ownership (and the responsibility to drop them)
+    /// goes to `dst`. Neither block is resized: `dst` must have enough spare capacity, both must be
+    /// unshared and they must be different blocks.
+    unsafe fn move_to(src: *mut Self, dst: *mut Self) {
+        debug_assert_ne!(src, dst);
+        let src_len = (*src).len();
+        let dst_len = (*dst).len();
+        debug_assert!(dst_len + src_len <= Self::capacity(dst));
+        ptr::copy_nonoverlapping(
+            Self::get_data(src),
+            Self::get_data_mut(dst).add(dst_len),
+            src_len,
+        );
+        (*src).set_len(0);
+        (*dst).set_len(dst_len + src_len);
+    }
+
+    /// Appends an element at the end, growing the block first if it is full.
+    ///
+    /// The block may move, in which case `*me` is updated to point to the new place. How much it
+    /// grows is up to the policy (doubling with the default one).
+    ///
+    /// The block must not be shared.
+    ///
+    /// # Panics
+    ///
+    /// If the block already holds the maximum number of elements.
+    unsafe fn push<G: GrowthPolicy>(me: *mut *mut Self, val: T) {
+        let block = Self::reserve::<G>(*me, 1);
+        *me = block;
+        Self::insert(block, (*block).len(), val);
+    }
+
+    /// Like [`reserve`][CoWecBlock::reserve], but returns an error instead of panicking or
+    /// aborting.
+    ///
+    /// On failure, the original block is left intact.
+    unsafe fn try_reserve<G: GrowthPolicy>(
+        me: *mut Self,
+        additional: usize,
+    ) -> Result<*mut Self, TryReserveError> {
+        let me_ref = &*me;
+        let required = me_ref.len().saturating_add(additional);
+        if required > Self::LEN_MASK {
+            return Err(TryReserveError::CapacityOverflow(CapacityError {
+                requested: required,
+                limit: Self::LEN_MASK,
+            }));
+        }
+        if required <= Self::capacity(me) {
+            Ok(me)
+        } else {
+            Self::try_resize(me, Self::grown_capacity::<G>(me, required))
+                .map_err(TryReserveError::AllocError)
         }
+    }
 
-        let me_ref = new_me.as_mut().unwrap();
-        me_ref.len = (me_ref.len & Self::LEN_MASK) | (cap_encoded << Self::CAP_OFFSET);
-        debug_assert_eq!(me_ref.capacity(), new_cap);
-        debug_assert_eq!(me_ref.len(), old_len);
+    /// Shrinks the capacity to fit at least `min_cap` (and all the current elements).
+    ///
+    /// If the block would fit exactly, it is made tight, otherwise the capacity is rounded up to
+    /// a power of two. Never grows the block. The block must be unique.
+    ///
+    /// Returns the (possibly moved) block.
+    unsafe fn shrink_to(me: *mut Self, min_cap: usize) -> *mut Self {
+        let me_ref = &*me;
+        debug_assert!(me_ref.rcell.is_unique());
+        let len = me_ref.len();
+        let target = min_cap.max(len);
+        // Power of two capacities (except for 1) have their own encoding.
+        let tight = target == len && (len < 2 || !len.is_power_of_two());
+        let new_cap = if tight {
+            len
+        } else {
+            target.next_power_of_two().max(2)
+        };
+        if new_cap >= Self::capacity(me) {
+            return me;
+        }
+        let new_me = Self::relocate(me, new_cap, tight);
+        if new_me.is_null() {
+            let layout = if tight {
+                Self::tight_layout(new_cap)
+            } else {
+                Self::layout(new_cap)
+            };
+            handle_alloc_error(layout);
+        }
         new_me
     }
 
+    /// Sets the length, keeping the capacity.
+    ///
+    /// The caller is responsible for the elements up to the new length being initialized and
+    /// for whatever was above it.
+    /// Only a power of two capacity is checked, the one of a tight block is out of reach of the
+    /// reference.
+    unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(Self::is_tight(self) || len <= Self::capacity(self));
+        debug_assert!(len <= Self::LEN_MASK);
+        self.len = L::from_usize((self.len.to_usize() & !Self::LEN_MASK) | len);
+    }
+
     unsafe fn insert(me: *mut Self, pos: usize, val: T) {
         let data = Self::get_data_mut(me);
         let me_ref = &mut *me;
-        debug_assert!(me_ref.len() < me_ref.capacity(), "Over current capacity");
+        debug_assert!(me_ref.len() < Self::capacity(me), "Over current capacity");
         debug_assert!(pos <= me_ref.len(), "Position out of range");
-        let new_len = me_ref.len() as u16 + 1;
-        debug_assert_eq!(new_len & Self::LEN_MASK, new_len, "Can't encode new length {:b}", new_len);
+        let new_len = me_ref.len() + 1;
+        debug_assert!(
+            new_len <= Self::LEN_MASK,
+            "Can't encode new length {:b}",
+            new_len
+        );
+        // The value is already constructed, so nothing between the shift and the write can
+        // panic and leave a hole at pos. The length is updated only once the slot is filled.
         let ptr_pos = data.add(pos);
         ptr::copy(ptr_pos, ptr_pos.add(1), me_ref.len() - pos);
         let elem = &mut *data.add(pos);
         ptr::write(elem.as_mut_ptr(), val);
-        me_ref.len = (me_ref.len & !Self::LEN_MASK) | new_len;
+        me_ref.set_len(new_len);
     }
 
     unsafe fn remove(me: *mut Self, pos: usize) -> T {
@@ -208,7 +701,7 @@ impl<R: RefCnt, T> CoWecBlock<R, T> {
         let ptr_pos = data.add(pos);
         let elem = ptr::read(ptr_pos).assume_init();
         ptr::copy(ptr_pos.add(1), ptr_pos, me_ref.len() - pos - 1);
-        me_ref.len -= 1; // len must be >0 by now, so no underflow and touching the capacity
+        me_ref.set_len(me_ref.len() - 1); // len must be >0 by now, so no underflow
         elem
     }
 
@@ -227,226 +720,3332 @@ impl<R: RefCnt, T> CoWecBlock<R, T> {
         let elem = data.add(pos);
         &mut *(*elem).as_mut_ptr()
     }
+
+    /// Makes sure we are the only owner of the block.
+    ///
+    /// If the block is shared, a private copy is made and our reference to the original is
+    /// released. Returns the block that is safe to modify (which may or may not be `me`).
+    unsafe fn make_unique(me: *mut Self) -> *mut Self
+    where
+        T: Clone,
+    {
+        if (*me).rcell.is_unique() {
+            return me;
+        }
+        let copy = Self::clone_block(me);
+        Self::dec_ref(me);
+        copy
+    }
+
+    /// Makes a deep copy of the block.
+    ///
+    /// The copy has the same capacity (except that a tight block is rounded up to a power of two,
+    /// as the copy is usually made to be modified) and a fresh reference count of 1.
+    ///
+    /// If cloning of any element panics, the already cloned elements are dropped and the new
+    /// block is freed.
+    unsafe fn clone_block(me: *const Self) -> *mut Self
+    where
+        T: Clone,
+    {
+        /// Disposes of the partial copy if we unwind out of the cloning.
+        struct Guard<R: RefCnt, T, L: LenCap>(*mut CoWecBlock<R, T, L>);
+
+        impl<R: RefCnt, T, L: LenCap> Drop for Guard<R, T, L> {
+            fn drop(&mut self) {
+                unsafe { CoWecBlock::dispose(self.0) };
+            }
+        }
+
+        let me_ref = &*me;
+        let len = me_ref.len();
+        let guard = Guard(Self::create(Self::capacity(me).next_power_of_two().max(2)));
+        let data = Self::get_data_mut(guard.0);
+        for i in 0..len {
+            ptr::write((*data.add(i)).as_mut_ptr(), Self::get(me, i).clone());
+            // Bump the length after each one, so the guard drops exactly the cloned ones.
+            (*guard.0).set_len(i + 1);
+        }
+        let copy = guard.0;
+        mem::forget(guard);
+        copy
+    }
+
+    /// Moves all the elements through `f` into a new block, freeing this one.
+    ///
+    /// The block must be unique. If `f` panics, both the elements not yet converted and the
+    /// already converted ones are dropped and both blocks are freed.
+    unsafe fn map_into<T2, F>(me: *mut Self, mut f: F) -> *mut CoWecBlock<R, T2, L>
+    where
+        F: FnMut(T) -> T2,
+    {
+        /// Owns the elements of `src` from `next` on, and the whole `dst` (unless null).
+        struct Guard<R: RefCnt, T, T2, L: LenCap> {
+            src: *mut CoWecBlock<R, T, L>,
+            next: usize,
+            len: usize,
+            dst: *mut CoWecBlock<R, T2, L>,
+        }
+
+        impl<R: RefCnt, T, T2, L: LenCap> Drop for Guard<R, T, T2, L> {
+            fn drop(&mut self) {
+                unsafe {
+                    let data = CoWecBlock::get_data_mut(self.src).cast::<T>();
+                    let rest =
+                        ptr::slice_from_raw_parts_mut(data.add(self.next), self.len - self.next);
+                    ptr::drop_in_place(rest);
+                    CoWecBlock::dispose(self.src);
+                    if !self.dst.is_null() {
+                        CoWecBlock::dispose(self.dst);
+                    }
+                }
+            }
+        }
+
+        debug_assert!((*me).rcell.is_unique());
+        let len = (*me).len();
+        // The guard owns the elements now.
+        (*me).set_len(0);
+        let mut guard = Guard {
+            src: me,
+            next: 0,
+            len,
+            dst: CoWecBlock::create(Self::capacity(me).next_power_of_two().max(2)),
+        };
+        let src = Self::get_data(me);
+        let dst = CoWecBlock::get_data_mut(guard.dst);
+        while guard.next < len {
+            let val = ptr::read((*src.add(guard.next)).as_ptr());
+            guard.next += 1;
+            ptr::write((*dst.add(guard.next - 1)).as_mut_ptr(), f(val));
+            (*guard.dst).set_len(guard.next);
+        }
+        mem::replace(&mut guard.dst, ptr::null_mut())
+    }
+}
+
+/// A [`CoWec`] with a single-threaded reference count.
+///
+/// As with [`Rc`][alloc::rc::Rc], it can't be sent to other threads:
+///
+/// ```compile_fail
+/// let c = dasture::CoWecRc::<u8, u8>::new_left();
+/// std::thread::spawn(move || drop(c));
+/// ```
+///
+/// Nor shared between them:
+///
+/// ```compile_fail
+/// fn is_sync<S: Sync>(_: &S) {}
+/// is_sync(&dasture::CoWecRc::<u8, u8>::new_left());
+/// ```
+pub type CoWecRc<T, U> = CoWec<RCell, T, U>;
+
+/// A [`CoWec`] with a thread-safe reference count.
+///
+/// It is both [`Send`] and [`Sync`] if the elements are:
+///
+/// ```
+/// let c = dasture::CoWecArc::<u8, u8>::new_left();
+/// std::thread::spawn(move || drop(c)).join().unwrap();
+/// ```
+pub type CoWecArc<T, U> = CoWec<AtomicRCell, T, U>;
+
+/// A [`CoWec`] with the large length encoding.
+///
+/// The blocks of the ordinary [`CoWec`] hold at most 4095 elements (the length is encoded in 12
+/// bits). This one uses 27 bits for the length (at the cost of 4 more bytes in the header), so
+/// each block can hold up to 2^27 - 1 elements.
+pub type LargeCoWec<R, T, U> = CoWec<R, T, U, u32>;
+
+/// A [`CoWec`] that only ever holds the left variant (or the stub).
+///
+/// The right variant is [`Infallible`], so it can't ever hold any elements.
+pub type SingleCoWec<R, T> = CoWec<R, T, Infallible>;
+
+/// A [`CoWec`] holding bytes in the left variant.
+///
+/// This is the one with the byte-buffer methods (eg. [`to_hex_left`][CoWec::to_hex_left]).
+pub type ByteCoWec<R, U> = CoWec<R, u8, U>;
+
+/// The commonly used types, for glob-importing.
+pub mod prelude {
+    pub use crate::{
+        AtomicRCell, ByteCoWec, CoWec, CoWecArc, CoWecRc, LargeCoWec, RCell, RefCnt, SingleCoWec,
+    };
 }
 
+/// A copy-on-write vector holding either `T`s (left) or `U`s (right), or nothing (stub).
+///
+/// # Limits
+///
+/// With the default `L = u16` length encoding, a block holds at most 4095 elements. Use
+/// [`LargeCoWec`] if more are needed.
+///
+/// # Growth
+///
+/// The `G` parameter decides how much the block grows once it is full, see [`GrowthPolicy`]. The
+/// default doubles it.
 #[repr(transparent)]
-pub struct CoWec<R, T, U>
+pub struct CoWec<R, T, U, L = u16, G = DoublingGrowth>
 where
     R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
 {
     ptr: usize,
-    _l: PhantomData<*mut CoWecBlock<R, T>>,
-    _r: PhantomData<*mut CoWecBlock<R, U>>,
+    _l: PhantomData<*mut CoWecBlock<R, T, L>>,
+    _r: PhantomData<*mut CoWecBlock<R, U, L>>,
+    _g: PhantomData<fn() -> G>,
 }
 
-impl<R, T, U> CoWec<R, T, U>
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
 where
     R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
 {
     pub fn new_stub() -> Self {
         Self {
             ptr: 0,
             _l: PhantomData,
             _r: PhantomData,
+            _g: PhantomData,
         }
     }
 
     pub fn new_left() -> Self {
-        let l = unsafe { CoWecBlock::<R, T>::create(2) };
-        Self {
-            ptr: l as usize,
-            _l: PhantomData,
-            _r: PhantomData,
-        }
+        Self::from_left_block(unsafe { CoWecBlock::create(2) })
     }
 
     pub fn new_right() -> Self {
-        let r = unsafe { CoWecBlock::<R, U>::create(2) };
-        Self {
-            ptr: r as usize + 1,
-            _l: PhantomData,
-            _r: PhantomData,
+        Self::from_right_block(unsafe { CoWecBlock::create(2) })
+    }
+
+    /// Creates a left `CoWec` with `n` default elements.
+    ///
+    /// This allocates the right-sized block up front.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is over the maximum length of a block.
+    pub fn with_default_left(n: usize) -> Self
+    where
+        T: Default,
+    {
+        let block = unsafe { CoWecBlock::<R, T, L>::create_for(n) };
+        // Owned by the CoWec right away, so the elements written so far get dropped if
+        // T::default panics.
+        let me = Self::from_left_block(block);
+        unsafe {
+            let data = CoWecBlock::get_data_mut(block);
+            for i in 0..n {
+                ptr::write((*data.add(i)).as_mut_ptr(), T::default());
+                (*block).set_len(i + 1);
+            }
+        }
+        me
+    }
+
+    /// Creates a left `CoWec` with `n` elements, all of them zero bytes.
+    ///
+    /// This allocates the right-sized block up front and zeroes it at once, which is faster than
+    /// [`with_default_left`][CoWec::with_default_left] for plain data types (like integers).
+    ///
+    /// # Safety
+    ///
+    /// All zero bytes must be a valid `T`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is over the maximum length of a block.
+    pub unsafe fn with_zeros_left(n: usize) -> Self {
+        let block = CoWecBlock::<R, T, L>::create_for(n);
+        let data = CoWecBlock::get_data_mut(block);
+        ptr::write_bytes(data, 0, n);
+        (*block).set_len(n);
+        Self::from_left_block(block)
+    }
+
+    /// Creates a left `CoWec` by cloning the elements of a slice.
+    ///
+    /// Unlike the [`From`] conversion, this doesn't panic if the slice is too long.
+    ///
+    /// # Errors
+    ///
+    /// If the slice is longer than the maximum length of a block.
+    pub fn try_from_exact_left(s: &[T]) -> Result<Self, CapacityError>
+    where
+        T: Clone,
+    {
+        let limit = CoWecBlock::<R, T, L>::LEN_MASK;
+        if s.len() > limit {
+            Err(CapacityError {
+                requested: s.len(),
+                limit,
+            })
+        } else {
+            Ok(Self::from(s))
         }
     }
 
+    /// Creates a left `CoWec` of `n` elements, without initializing them.
+    ///
+    /// # Safety
+    ///
+    /// All the elements have to be initialized (eg. by writing through
+    /// [`as_left_mut_ptr`][CoWec::as_left_mut_ptr]) before the `CoWec` is used in any other way,
+    /// including being cloned or dropped.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is over the maximum length of a block.
+    pub unsafe fn uninit_left(n: usize) -> Self {
+        let block = CoWecBlock::<R, T, L>::create_for(n);
+        (*block).set_len(n);
+        Self::from_left_block(block)
+    }
+
+    /// Marks the elements of a `CoWec` created by [`uninit_left`][CoWec::uninit_left] as
+    /// initialized.
+    ///
+    /// This does nothing at runtime, it only documents the point in code where the
+    /// initialization is complete.
+    ///
+    /// # Safety
+    ///
+    /// All the elements must have been initialized. This can't be checked (not even in debug
+    /// builds, only the variant is).
+    pub unsafe fn assume_init_left(self) -> Self {
+        debug_assert!(self.is_left(), "assume_init_left on a non-left CoWec");
+        self
+    }
+
     pub fn is_stub(&self) -> bool {
         self.ptr == 0
     }
 
     pub fn is_left(&self) -> bool {
-        self.ptr % 2 == 0 && !self.is_stub()
+        self.ptr & 1 == 0 && !self.is_stub()
     }
 
     pub fn is_right(&self) -> bool {
         !self.is_left() && !self.is_stub()
     }
-}
 
-impl<R, T, U> Clone for CoWec<R, T, U>
-where
-    R: RefCnt,
-{
-    // FIXME: But we may want to provide shrinking and it would be great if we could do it when
-    // getting shared.
-    fn clone(&self) -> Self {
-        let ptr = if self.is_left() {
-            unsafe { CoWecBlock::<R, T>::inc_ref(self.ptr as *mut _) as usize }
-        } else if self.is_right() {
-            unsafe { CoWecBlock::<R, U>::inc_ref((self.ptr - 1) as *mut _) as usize + 1 }
-        } else {
-            0
-        };
+    /// Checks if both point to the same block (and hold the same variant).
+    ///
+    /// Like [`Rc::ptr_eq`][alloc::rc::Rc::ptr_eq], this is cheaper than comparing the elements.
+    /// Clones share the block until one of them is modified. All stubs are equal to each other.
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        a.ptr == b.ptr
+    }
+
+    /// Wraps a block into the left variant, taking over its reference.
+    fn from_left_block(block: *mut CoWecBlock<R, T, L>) -> Self {
         Self {
-            ptr,
+            ptr: block as usize,
             _l: PhantomData,
             _r: PhantomData,
+            _g: PhantomData,
         }
     }
-}
 
-impl<R, T, U> Drop for CoWec<R, T, U>
-where
-    R: RefCnt,
-{
-    fn drop(&mut self) {
+    fn left_block(&self) -> Option<*mut CoWecBlock<R, T, L>> {
         if self.is_left() {
-            unsafe { CoWecBlock::<R, T>::dec_ref(self.ptr as *mut _) }
-        } else if self.is_right() {
-            unsafe { CoWecBlock::<R, U>::dec_ref((self.ptr - 1) as *mut _) }
+            Some(self.ptr as *mut _)
+        } else {
+            None
         }
     }
-}
+
+    /// Wraps a block into the right variant, taking over its reference.
+    fn from_right_block(block: *mut CoWecBlock<R, U, L>) -> Self {
+        Self {
+            ptr: block as usize + 1,
+            _l: PhantomData,
+            _r: PhantomData,
+            _g: PhantomData,
+        }
+    }
+
+    fn right_block(&self) -> Option<*mut CoWecBlock<R, U, L>> {
+        if self.is_right() {
+            Some((self.ptr - 1) as *mut _)
+        } else {
+            None
+        }
+    }
+
+    /// Number used to order the variants.
+    fn variant_rank(&self) -> u8 {
+        if self.is_stub() {
+            0
+        } else if self.is_left() {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn left_slice(&self) -> Option<&[T]> {
+        self.left_block()
+            .map(|block| unsafe { CoWecBlock::as_slice(block) })
+    }
+
+    fn right_slice(&self) -> Option<&[U]> {
+        self.right_block()
+            .map(|block| unsafe { CoWecBlock::as_slice(block) })
+    }
+
+    /// Makes sure the left block is not shared, so it can be modified in place.
+    ///
+    /// Returns `None` if this is not the left variant.
+    fn make_mut_left(&mut self) -> Option<*mut CoWecBlock<R, T, L>>
+    where
+        T: Clone,
+    {
+        let block = self.left_block()?;
+        let block = unsafe { CoWecBlock::make_unique(block) };
+        self.ptr = block as usize;
+        Some(block)
+    }
+
+    /// Makes sure the right block is not shared, so it can be modified in place.
+    ///
+    /// Returns `None` if this is not the right variant.
+    fn make_mut_right(&mut self) -> Option<*mut CoWecBlock<R, U, L>>
+    where
+        U: Clone,
+    {
+        let block = self.right_block()?;
+        let block = unsafe { CoWecBlock::make_unique(block) };
+        self.ptr = block as usize + 1;
+        Some(block)
+    }
+
+    fn left_slice_mut(&mut self) -> Option<&mut [T]>
+    where
+        T: Clone,
+    {
+        self.make_mut_left()
+            .map(|block| unsafe { CoWecBlock::as_slice_mut(block) })
+    }
+
+    fn right_slice_mut(&mut self) -> Option<&mut [U]>
+    where
+        U: Clone,
+    {
+        self.make_mut_right()
+            .map(|block| unsafe { CoWecBlock::as_slice_mut(block) })
+    }
+
+    /// Runs one of the closures on the elements of whichever variant is active.
+    ///
+    /// Returns `None` for the stub.
+    pub fn either<O, FL, FR>(&self, left_fn: FL, right_fn: FR) -> Option<O>
+    where
+        FL: FnOnce(&[T]) -> O,
+        FR: FnOnce(&[U]) -> O,
+    {
+        if let Some(left) = self.left_slice() {
+            Some(left_fn(left))
+        } else {
+            self.right_slice().map(right_fn)
+        }
+    }
+
+    /// Like [`either`][CoWec::either], but produces the default value for the stub.
+    pub fn either_or_default<O, FL, FR>(&self, left_fn: FL, right_fn: FR) -> O
+    where
+        O: Default,
+        FL: FnOnce(&[T]) -> O,
+        FR: FnOnce(&[U]) -> O,
+    {
+        self.either(left_fn, right_fn).unwrap_or_default()
+    }
+
+    /// Runs one of the closures on the mutable elements of whichever variant is active.
+    ///
+    /// The block is unshared (copied) first if needed. Returns `None` for the stub.
+    pub fn either_mut<O, FL, FR>(&mut self, left_fn: FL, right_fn: FR) -> Option<O>
+    where
+        T: Clone,
+        U: Clone,
+        FL: FnOnce(&mut [T]) -> O,
+        FR: FnOnce(&mut [U]) -> O,
+    {
+        if self.is_left() {
+            self.left_slice_mut().map(left_fn)
+        } else {
+            self.right_slice_mut().map(right_fn)
+        }
+    }
+
+    /// Iterates over all the overlapping windows of `size` elements of the left variant.
+    ///
+    /// Works like [`slice::windows`]. The iterator is empty for the other variants.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0.
+    pub fn windows_left(&self, size: usize) -> WindowsLeft<'_, R, T, U, L> {
+        WindowsLeft::new(self.left_slice().unwrap_or(&[]).windows(size))
+    }
+
+    /// Iterates over the left variant in non-overlapping chunks of `size` elements.
+    ///
+    /// Works like [`slice::chunks`] (the last chunk may be shorter). The iterator is empty for the
+    /// other variants.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0.
+    pub fn chunks_left(&self, size: usize) -> ChunksLeft<'_, R, T, U, L> {
+        ChunksLeft::new(self.left_slice().unwrap_or(&[]).chunks(size))
+    }
+
+    /// Iterates over the left variant in non-overlapping mutable chunks of `size` elements.
+    ///
+    /// Works like [`slice::chunks_mut`]. The block is copied first if it is shared. The iterator is
+    /// empty for the other variants.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is 0.
+    pub fn chunks_left_mut(&mut self, size: usize) -> ChunksLeftMut<'_, R, T, U, L>
+    where
+        T: Clone,
+    {
+        ChunksLeftMut::new(self.left_slice_mut().unwrap_or(&mut []).chunks_mut(size))
+    }
+
+    /// Iterates over all the overlapping windows of `size` elements of the right variant.
+    ///
+    /// The right-variant counterpart of [`windows_left`][CoWec::windows_left].
+    pub fn windows_right(&self, size: usize) -> WindowsRight<'_, R, T, U, L> {
+        WindowsRight::new(self.right_slice().unwrap_or(&[]).windows(size))
+    }
+
+    /// Iterates over the right variant in non-overlapping chunks of `size` elements.
+    ///
+    /// The right-variant counterpart of [`chunks_left`][CoWec::chunks_left].
+    pub fn chunks_right(&self, size: usize) -> ChunksRight<'_, R, T, U, L> {
+        ChunksRight::new(self.right_slice().unwrap_or(&[]).chunks(size))
+    }
+
+    /// Iterates over the right variant in non-overlapping mutable chunks of `size` elements.
+    ///
+    /// The right-variant counterpart of [`chunks_left_mut`][CoWec::chunks_left_mut].
+    pub fn chunks_right_mut(&mut self, size: usize) -> ChunksRightMut<'_, R, T, U, L>
+    where
+        U: Clone,
+    {
+        ChunksRightMut::new(self.right_slice_mut().unwrap_or(&mut []).chunks_mut(size))
+    }
+
+    /// Pairs up the elements of the left variant of `left` with the right variant of `right`.
+    ///
+    /// Useful when two `CoWec`s are used as parallel arrays of keys and values. Like with
+    /// [`Iterator::zip`], the iteration stops at the end of the shorter one, a `CoWec` holding
+    /// the other variant counts as empty.
+    pub fn zip_left_right<'a>(left: &'a Self, right: &'a Self) -> ZipLeftRight<'a, T, U> {
+        let left = left.left_slice().unwrap_or_default();
+        let right = right.right_slice().unwrap_or_default();
+        ZipLeftRight::new(left.iter().zip(right))
+    }
+
+    /// Moves all the elements of `other` to the end of `self`, leaving `other` empty.
+    ///
+    /// Both need to be the left variant, otherwise `false` is returned and nothing happens. If
+    /// any of the blocks is shared, it is copied first.
+    ///
+    /// The elements are moved over in bulk, but the blocks themselves are never merged in place,
+    /// even if they happen to lie next to each other in memory ‒ they are separate allocations
+    /// and each needs to go back to the allocator as such.
+    ///
+    /// # Panics
+    ///
+    /// If the combined length doesn't fit into a block.
+    pub fn coalesce_left(&mut self, other: &mut Self) -> bool
+    where
+        T: Clone,
+    {
+        if !self.is_left() || !other.is_left() {
+            return false;
+        }
+        let other_block = other.make_mut_left().unwrap();
+        let block = self.make_mut_left().unwrap();
+        unsafe {
+            let block = CoWecBlock::reserve::<G>(block, (*other_block).len());
+            self.ptr = block as usize;
+            CoWecBlock::move_to(other_block, block);
+        }
+        true
+    }
+
+    /// Appends clones of the left elements of `other` to the end of the left variant.
+    ///
+    /// Nothing happens unless both are the left variant. The block is copied first if it is
+    /// shared (even with `other`, which is therefore never modified while being read). The
+    /// elements are cloned straight into the block, without any temporary buffer. If a clone
+    /// panics, the elements cloned so far stay appended.
+    ///
+    /// # Panics
+    ///
+    /// If the combined length doesn't fit into a block.
+    pub fn extend_from_cowec_left(&mut self, other: &Self)
+    where
+        T: Clone,
+    {
+        let src = match other.left_slice() {
+            Some(src) if !src.is_empty() => src,
+            _ => return,
+        };
+        if let Some(block) = self.make_mut_left() {
+            unsafe {
+                let block = CoWecBlock::reserve::<G>(block, src.len());
+                self.ptr = block as usize;
+                let len = (*block).len();
+                for (i, elem) in src.iter().enumerate() {
+                    CoWecBlock::insert(block, len + i, elem.clone());
+                }
+            }
+        }
+    }
+
+    /// Like [`extend_from_cowec_left`][CoWec::extend_from_cowec_left], but copies the elements
+    /// in bulk.
+    pub fn extend_from_cowec_left_copy(&mut self, other: &Self)
+    where
+        T: Copy,
+    {
+        let src = match other.left_slice() {
+            Some(src) if !src.is_empty() => src,
+            _ => return,
+        };
+        if let Some(block) = self.make_mut_left() {
+            unsafe {
+                let block = CoWecBlock::reserve::<G>(block, src.len());
+                self.ptr = block as usize;
+                let len = (*block).len();
+                ptr::copy_nonoverlapping(
+                    src.as_ptr(),
+                    CoWecBlock::get_data_mut(block).add(len).cast(),
+                    src.len(),
+                );
+                (*block).set_len(len + src.len());
+            }
+        }
+    }
+
+    /// Inserts an element at position `pos` of the left variant, shifting the later elements.
+    ///
+    /// The block grows as needed and is copied first if it is shared.
+    ///
+    /// # Errors
+    ///
+    /// If this is not the left variant or if the block already holds the maximum number of
+    /// elements.
+    ///
+    /// # Panics
+    ///
+    /// If `pos > len`.
+    pub fn insert_left(&mut self, pos: usize, val: T) -> Result<(), InsertError>
+    where
+        T: Clone,
+    {
+        let block = self.left_block().ok_or(InsertError::WrongVariant)?;
+        let len = unsafe { (*block).len() };
+        assert!(
+            pos <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            pos,
+            len
+        );
+        if len >= CoWecBlock::<R, T, L>::LEN_MASK {
+            return Err(InsertError::Full);
+        }
+        let block = self.make_mut_left().unwrap();
+        unsafe {
+            let block = CoWecBlock::reserve::<G>(block, 1);
+            self.ptr = block as usize;
+            CoWecBlock::insert(block, pos, val);
+        }
+        Ok(())
+    }
+
+    /// Appends an element to the end of the left variant.
+    ///
+    /// See [`insert_left`][CoWec::insert_left].
+    pub fn push_left(&mut self, val: T) -> Result<(), InsertError>
+    where
+        T: Clone,
+    {
+        let len = self.len_left().ok_or(InsertError::WrongVariant)?;
+        if len >= CoWecBlock::<R, T, L>::LEN_MASK {
+            return Err(InsertError::Full);
+        }
+        let mut block = self.make_mut_left().unwrap();
+        unsafe { CoWecBlock::push::<G>(&mut block, val) };
+        self.ptr = block as usize;
+        Ok(())
+    }
+
+    /// Removes and returns the element at position `pos` of the left variant, shifting the later
+    /// elements.
+    ///
+    /// The block is copied first if it is shared.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant or if `pos >= len`.
+    pub fn remove_left(&mut self, pos: usize) -> T
+    where
+        T: Clone,
+    {
+        let block = self
+            .left_block()
+            .expect("remove_left called on a CoWec not holding the left variant");
+        let len = unsafe { (*block).len() };
+        assert!(
+            pos < len,
+            "removal index (is {}) should be < len (is {})",
+            pos,
+            len
+        );
+        let block = self.make_mut_left().unwrap();
+        unsafe { CoWecBlock::remove(block, pos) }
+    }
+
+    /// Removes the last element of the left variant.
+    ///
+    /// Returns `None` if this is not the left variant or if it is empty.
+    pub fn pop_left(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        match self.len_left()? {
+            0 => None,
+            len => Some(self.remove_left(len - 1)),
+        }
+    }
+
+    /// Makes sure at least `additional` more elements fit into the left block without
+    /// reallocating.
+    ///
+    /// The block is copied first if it is shared. The capacities are powers of two, so this may
+    /// reserve more.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant or the resulting length would be over the limit of a block.
+    pub fn reserve_left(&mut self, additional: usize)
+    where
+        T: Clone,
+    {
+        self.reserve_left_by::<G>(additional)
+    }
+
+    fn reserve_left_by<P: GrowthPolicy>(&mut self, additional: usize)
+    where
+        T: Clone,
+    {
+        match self.try_reserve_left_by::<P>(additional) {
+            Ok(()) => (),
+            Err(TryReserveError::AllocError(layout)) => handle_alloc_error(layout),
+            Err(TryReserveError::WrongVariant) => {
+                panic!("reserve_left called on a CoWec not holding the left variant")
+            }
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Reserves room for exactly `additional` more elements of the left variant.
+    ///
+    /// This ignores the growth policy. If the block needs to grow, the new capacity is exactly the
+    /// current length plus `additional` (even if it is not a power of two). Like with
+    /// [`Vec::reserve_exact`], nothing happens if there's already enough room.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant or the resulting length would be over the limit of a block.
+    pub fn reserve_left_exact(&mut self, additional: usize)
+    where
+        T: Clone,
+    {
+        let block = self
+            .make_mut_left()
+            .expect("reserve_left_exact called on a CoWec not holding the left variant");
+        self.ptr = unsafe { CoWecBlock::reserve_exact(block, additional) } as usize;
+    }
+
+    /// Like [`reserve_left`][CoWec::reserve_left], but returns an error instead of panicking.
+    ///
+    /// Also reports failures of the allocator, instead of aborting (except for copying of a
+    /// shared block).
+    ///
+    /// # Errors
+    ///
+    /// If this is not the left variant, the resulting length would be over the limit or the
+    /// allocation fails. The content is left unchanged in such case.
+    pub fn try_reserve_left(&mut self, additional: usize) -> Result<(), TryReserveError>
+    where
+        T: Clone,
+    {
+        self.try_reserve_left_by::<G>(additional)
+    }
+
+    fn try_reserve_left_by<P: GrowthPolicy>(
+        &mut self,
+        additional: usize,
+    ) -> Result<(), TryReserveError>
+    where
+        T: Clone,
+    {
+        let block = self.make_mut_left().ok_or(TryReserveError::WrongVariant)?;
+        self.ptr = unsafe { CoWecBlock::try_reserve::<P>(block, additional) }? as usize;
+        Ok(())
+    }
+
+    /// Shrinks the left block to hold exactly its current elements.
+    ///
+    /// Does nothing if the block is shared (it would take a copy, which needs more memory, not
+    /// less) or if this is not the left variant.
+    pub fn shrink_to_fit_left(&mut self) {
+        self.shrink_to_left(0);
+    }
+
+    /// Shrinks the capacity of the left block to at least `min_capacity`.
+    ///
+    /// The capacity never goes below the current length and is rounded up to a power of two
+    /// (unless it fits the elements exactly). Does nothing if the block is shared, if it is
+    /// already smaller or if this is not the left variant.
+    pub fn shrink_to_left(&mut self, min_capacity: usize) {
+        if let Some(block) = self.left_block() {
+            if unsafe { (*block).rcell.is_unique() } {
+                self.ptr = unsafe { CoWecBlock::shrink_to(block, min_capacity) } as usize;
+            }
+        }
+    }
+
+    /// Shrinks the right block to hold exactly its current elements.
+    ///
+    /// The counterpart of [`shrink_to_fit_left`][CoWec::shrink_to_fit_left].
+    pub fn shrink_to_fit_right(&mut self) {
+        self.shrink_to_right(0);
+    }
+
+    /// Shrinks the capacity of the right block to at least `min_capacity`.
+    ///
+    /// The counterpart of [`shrink_to_left`][CoWec::shrink_to_left].
+    pub fn shrink_to_right(&mut self, min_capacity: usize) {
+        if let Some(block) = self.right_block() {
+            if unsafe { (*block).rcell.is_unique() } {
+                self.ptr = unsafe { CoWecBlock::shrink_to(block, min_capacity) } as usize + 1;
+            }
+        }
+    }
+
+    /// The number of elements of the left variant.
+    ///
+    /// Returns `None` if this is not the left variant.
+    pub fn len_left(&self) -> Option<usize> {
+        self.left_block().map(|block| unsafe { (*block).len() })
+    }
+
+    /// The number of elements of the right variant.
+    ///
+    /// Returns `None` if this is not the right variant.
+    pub fn len_right(&self) -> Option<usize> {
+        self.right_block().map(|block| unsafe { (*block).len() })
+    }
+
+    /// How many elements the left block can hold without reallocating.
+    ///
+    /// Returns `None` if this is not the left variant.
+    pub fn capacity_left(&self) -> Option<usize> {
+        self.left_block()
+            .map(|block| unsafe { CoWecBlock::capacity(block) })
+    }
+
+    /// How many elements the right block can hold without reallocating.
+    ///
+    /// Returns `None` if this is not the right variant.
+    pub fn capacity_right(&self) -> Option<usize> {
+        self.right_block()
+            .map(|block| unsafe { CoWecBlock::capacity(block) })
+    }
+
+    /// The number of elements of whichever variant is active (0 for the stub).
+    pub fn len(&self) -> usize {
+        self.len_left().or_else(|| self.len_right()).unwrap_or(0)
+    }
+
+    /// Checks if there are no elements (in whichever variant is active).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if the left variant contains an element equal to `x`.
+    ///
+    /// Returns `false` for the other variants.
+    pub fn contains_left(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.left_slice().is_some_and(|left| left.contains(x))
+    }
+
+    /// Returns the index of the first element of the left variant matching the predicate.
+    ///
+    /// Returns `None` if there's no such element or if this is not the left variant.
+    pub fn position_left<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.left_slice()?.iter().position(predicate)
+    }
+
+    /// Returns the index of the last element of the left variant matching the predicate.
+    ///
+    /// Like [`position_left`][CoWec::position_left], but searches from the end.
+    pub fn rposition_left<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.left_slice()?.iter().rposition(predicate)
+    }
+
+    /// Overwrites all the elements of the left variant with clones of `value`.
+    ///
+    /// The block is copied first if it is shared. Does nothing to the other variants. If a clone
+    /// panics, the elements before it already hold the new value, the rest the old ones.
+    pub fn fill_left(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        if let Some(left) = self.left_slice_mut() {
+            left.fill(value);
+        }
+    }
+
+    /// Overwrites all the elements of the left variant with values returned by `f`.
+    ///
+    /// Like [`fill_left`][CoWec::fill_left], but calls `f` for each element.
+    pub fn fill_left_with<F>(&mut self, f: F)
+    where
+        F: FnMut() -> T,
+        T: Clone,
+    {
+        if let Some(left) = self.left_slice_mut() {
+            left.fill_with(f);
+        }
+    }
+
+    /// Overwrites all the elements of the right variant with clones of `value`.
+    ///
+    /// The counterpart of [`fill_left`][CoWec::fill_left].
+    pub fn fill_right(&mut self, value: U)
+    where
+        U: Clone,
+    {
+        if let Some(right) = self.right_slice_mut() {
+            right.fill(value);
+        }
+    }
+
+    /// Overwrites all the elements of the right variant with values returned by `f`.
+    ///
+    /// The counterpart of [`fill_left_with`][CoWec::fill_left_with].
+    pub fn fill_right_with<F>(&mut self, f: F)
+    where
+        F: FnMut() -> U,
+        U: Clone,
+    {
+        if let Some(right) = self.right_slice_mut() {
+            right.fill_with(f);
+        }
+    }
+
+    /// Replaces the `range` of the left elements with the ones from `replace_with`.
+    ///
+    /// Returns the removed elements. Unlike [`Vec::splice`], this is done eagerly ‒ the
+    /// replacement is collected first, so a panicking iterator leaves `self` unchanged. The block
+    /// is copied first if it is shared.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant, if the range is out of bounds or if the result doesn't
+    /// fit into a block.
+    pub fn splice_left<B, I>(&mut self, range: B, replace_with: I) -> SpliceLeft<T>
+    where
+        B: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+        T: Clone,
+    {
+        let len = self
+            .len_left()
+            .expect("splice_left called on a CoWec not holding the left variant");
+        let (start, end) = resolve_range(range, len);
+        let mut replacement: Vec<T> = replace_with.into_iter().collect();
+        let new_len = len - (end - start) + replacement.len();
+        let limit = CoWecBlock::<R, T, L>::LEN_MASK;
+        assert!(
+            new_len <= limit,
+            "Length {} over the limit of {}",
+            new_len,
+            limit
+        );
+        let block = self.make_mut_left().unwrap();
+        let tail = len - end;
+        let mut removed = Vec::with_capacity(end - start);
+        unsafe {
+            // Grow while all the elements are still owned by the block (and below its length).
+            let block = CoWecBlock::reserve::<G>(block, new_len.saturating_sub(len));
+            self.ptr = block as usize;
+            let data = CoWecBlock::get_data_mut(block).cast::<T>();
+            ptr::copy_nonoverlapping(data.add(start), removed.as_mut_ptr(), end - start);
+            removed.set_len(end - start);
+            // Nothing can panic or allocate from here on, so the tail being temporarily not owned
+            // by anyone is fine.
+            (*block).set_len(start);
+            ptr::copy(data.add(end), data.add(start + replacement.len()), tail);
+            ptr::copy_nonoverlapping(replacement.as_ptr(), data.add(start), replacement.len());
+            replacement.set_len(0);
+            (*block).set_len(new_len);
+        }
+        SpliceLeft::new(removed.into_iter())
+    }
+
+    /// Copies the elements of the `src` range to start at `dst`, within the left variant.
+    ///
+    /// The ranges may overlap. This is the [`copy_within`][slice::copy_within] of the left
+    /// elements, the block is copied first if it is shared.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant, if the range is out of bounds or if `dst + range length`
+    /// is over the length.
+    pub fn copy_within_left<S>(&mut self, src: S, dst: usize)
+    where
+        S: RangeBounds<usize>,
+        T: Copy,
+    {
+        self.left_slice_mut()
+            .expect("copy_within_left called on a CoWec not holding the left variant")
+            .copy_within(src, dst);
+    }
+
+    /// Splits the left variant into two read-only views, `[0, mid)` and `[mid, len)`.
+    ///
+    /// Both share the block with `self` (no elements are copied). Returns `None` if this is not
+    /// the left variant or if the reference count can't take two more references.
+    ///
+    /// # Panics
+    ///
+    /// If `mid > len`.
+    #[allow(clippy::type_complexity)]
+    pub fn split_borrowed_left(
+        &self,
+        mid: usize,
+    ) -> Option<(SlicedBlock<R, T, L>, SlicedBlock<R, T, L>)> {
+        let block = self.left_block()?;
+        let len = self.len_left().unwrap();
+        assert!(mid <= len, "mid (is {}) should be <= len (is {})", mid, len);
+        unsafe {
+            if !(*block).rcell.inc_ref() {
+                return None;
+            }
+            if !(*block).rcell.inc_ref() {
+                // Can't drop to 0, self still holds its reference
+                (*block).rcell.dec_ref();
+                return None;
+            }
+        }
+        let block = block as *mut _;
+        Some((
+            SlicedBlock::new(block, 0, mid),
+            SlicedBlock::new(block, mid, len),
+        ))
+    }
+
+    /// Clones the left variant, but only if it can be done by sharing the block.
+    ///
+    /// Returns `None` if the reference count is already at its maximum (where a regular clone
+    /// would have to copy the block) or if this is not the left variant.
+    pub fn try_clone_left(&self) -> Option<Self> {
+        let block = self.left_block()?;
+        if unsafe { (*block).rcell.inc_ref() } {
+            Some(Self::from_left_block(block))
+        } else {
+            None
+        }
+    }
+
+    /// Clones the left variant into a fresh block, never sharing it.
+    ///
+    /// The result can be modified without triggering a copy (unlike a [`clone`][Clone::clone]
+    /// that shares the block). Returns `None` if this is not the left variant.
+    pub fn deep_clone_left(&self) -> Option<Self>
+    where
+        T: Clone,
+    {
+        let block = self.left_block()?;
+        Some(Self::from_left_block(unsafe {
+            CoWecBlock::clone_block(block)
+        }))
+    }
+
+    /// Leaks the left variant, returning a slice that lives forever.
+    ///
+    /// Like [`Box::leak`], the memory is **never freed**. Other handles sharing the block keep
+    /// working, but as the leaked reference is never released, they'll copy the block before
+    /// modifying it.
+    ///
+    /// Returns `None` (dropping `self`) if this is not the left variant.
+    pub fn leak_left(self) -> Option<&'static [T]>
+    where
+        T: 'static,
+    {
+        let block = self.left_block()?;
+        mem::forget(self);
+        Some(unsafe { CoWecBlock::as_slice(block) })
+    }
+
+    /// Turns the left variant into a boxed slice.
+    ///
+    /// The elements are moved out if the block is not shared, cloned otherwise. Returns `None`
+    /// (dropping `self`) if this is not the left variant.
+    pub fn into_boxed_slice_left(mut self) -> Option<Box<[T]>>
+    where
+        T: Clone,
+    {
+        let block = self.make_mut_left()?;
+        unsafe {
+            let len = (*block).len();
+            let mut v = Vec::with_capacity(len);
+            ptr::copy_nonoverlapping(CoWecBlock::get_data(block).cast(), v.as_mut_ptr(), len);
+            // Moved to the box, the block is left with nothing to drop.
+            (*block).set_len(0);
+            v.set_len(len);
+            Some(v.into_boxed_slice())
+        }
+    }
+
+    /// Compares the left variants bit by bit.
+    ///
+    /// Two `CoWec`s sharing the same block are equal without looking at the content at all,
+    /// otherwise the raw bytes of the elements are compared. This is faster than `==` for types
+    /// where bitwise equality is the same as semantic equality (eg. integers). Returns `false`
+    /// if any of them is not the left variant.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    pub unsafe fn structural_eq_left(&self, other: &Self) -> bool
+    where
+        T: Copy,
+    {
+        let (me, other) = match (self.left_block(), other.left_block()) {
+            (Some(me), Some(other)) => (me, other),
+            _ => return false,
+        };
+        if ptr::eq(me, other) {
+            return true;
+        }
+        let len = (*me).len();
+        if len != (*other).len() {
+            return false;
+        }
+        let bytes = |block| {
+            slice::from_raw_parts(
+                CoWecBlock::get_data(block).cast::<u8>(),
+                len * mem::size_of::<T>(),
+            )
+        };
+        bytes(me) == bytes(other)
+    }
+
+    /// Converts the right variant into the left one, mapping each element through `f`.
+    ///
+    /// The left variant is left as it is (`f` is not applied), a stub becomes an empty left. If
+    /// the right block is shared, its elements are cloned first.
+    ///
+    /// If `f` panics, `self` is left as a stub and all the elements (both the converted and the
+    /// not yet converted ones) are dropped.
+    pub fn transform_to_left<F>(&mut self, f: F)
+    where
+        F: FnMut(U) -> T,
+        U: Clone,
+    {
+        if self.is_left() {
+            return;
+        }
+        let block = match self.make_mut_right() {
+            Some(block) => block,
+            None => {
+                *self = Self::new_left();
+                return;
+            }
+        };
+        // The block is owned by the conversion now, don't let a panic point us to it.
+        self.ptr = 0;
+        *self = Self::from_left_block(unsafe { CoWecBlock::map_into(block, f) });
+    }
+
+    /// Converts the left variant into the right one, mapping each element through `f`.
+    ///
+    /// The mirror image of [`transform_to_left`][CoWec::transform_to_left].
+    pub fn transform_to_right<F>(&mut self, f: F)
+    where
+        F: FnMut(T) -> U,
+        T: Clone,
+    {
+        if self.is_right() {
+            return;
+        }
+        let block = match self.make_mut_left() {
+            Some(block) => block,
+            None => {
+                *self = Self::new_right();
+                return;
+            }
+        };
+        self.ptr = 0;
+        *self = Self::from_right_block(unsafe { CoWecBlock::map_into(block, f) });
+    }
+
+    /// Computes a fast (non-cryptographic) fingerprint of the left variant's raw bytes.
+    ///
+    /// Useful for cheap change detection or deduplication. The variants other than left all
+    /// produce the fingerprint of no data (the same as an empty left).
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    pub unsafe fn fingerprint_left(&self) -> u64
+    where
+        T: Copy,
+    {
+        checksum::fx_hash(self.left_bytes())
+    }
+
+    /// Copies the raw bytes of the left elements into a vector.
+    ///
+    /// The other variants produce an empty vector. This is meant for plain data types (like
+    /// integers) that can be sent over the network as they are.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    pub unsafe fn into_bytes_left(self) -> Vec<u8>
+    where
+        T: Copy,
+    {
+        self.left_bytes().to_vec()
+    }
+
+    /// Creates a left `CoWec` from the raw bytes of the elements.
+    ///
+    /// The bytes don't have to be aligned. Returns `None` if the length is not a multiple of the
+    /// size of `T` or if `T` is zero-sized (the number of elements is unknown).
+    ///
+    /// # Safety
+    ///
+    /// Any bit pattern must be a valid `T` (or at least the ones in `bytes` must be).
+    ///
+    /// # Panics
+    ///
+    /// If the number of elements is over the maximum length of a block.
+    pub unsafe fn from_bytes_left(bytes: &[u8]) -> Option<Self>
+    where
+        T: Copy,
+    {
+        let size = mem::size_of::<T>();
+        if size == 0 || bytes.len() % size != 0 {
+            return None;
+        }
+        let len = bytes.len() / size;
+        let block = CoWecBlock::<R, T, L>::create_for(len);
+        let data = CoWecBlock::get_data_mut(block).cast::<u8>();
+        ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len());
+        (*block).set_len(len);
+        Some(Self::from_left_block(block))
+    }
+
+    /// The raw bytes of the left elements (empty for other variants).
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    unsafe fn left_bytes(&self) -> &[u8]
+    where
+        T: Copy,
+    {
+        match self.left_block() {
+            Some(block) => slice::from_raw_parts(
+                CoWecBlock::get_data(block).cast::<u8>(),
+                (*block).len() * mem::size_of::<T>(),
+            ),
+            None => &[],
+        }
+    }
+
+    /// Computes the Adler-32 checksum (as used by zlib) of the left variant's raw bytes.
+    ///
+    /// Like with [`fingerprint_left`][CoWec::fingerprint_left], the other variants produce the
+    /// checksum of no data.
+    ///
+    /// # Safety
+    ///
+    /// `T` must not contain any padding or otherwise uninitialized bytes.
+    pub unsafe fn adler32_left(&self) -> u32
+    where
+        T: Copy,
+    {
+        checksum::adler32(self.left_bytes())
+    }
+
+    /// Views the `CoWec` as one with a different right type.
+    ///
+    /// This is possible only if there's no right block (the `CoWec` is left or a stub). The right
+    /// type then exists only in the type system and can be swapped without touching anything in
+    /// memory, as the pointer and the left block stay the same. Returns `None` for the right
+    /// variant.
+    pub fn as_transmuted_right<U2>(&self) -> Option<&CoWec<R, T, U2, L, G>> {
+        if self.is_right() {
+            None
+        } else {
+            // Both are repr(transparent) over the same pointer, only the PhantomData differs.
+            Some(unsafe { &*(self as *const Self as *const CoWec<R, T, U2, L, G>) })
+        }
+    }
+
+    /// Returns a read-only pointer to the first element of the left variant.
+    ///
+    /// The pointer is valid until the `CoWec` is modified or dropped. Writing through it is not
+    /// allowed, as the block may be shared (see [`as_left_mut_ptr`][CoWec::as_left_mut_ptr]).
+    /// Returns `None` if this is not the left variant.
+    pub fn as_left_ptr(&self) -> Option<*const T> {
+        let block = self.left_block()?;
+        Some(unsafe { CoWecBlock::get_data(block).cast() })
+    }
+
+    /// Reconstructs a left `CoWec` from its parts.
+    ///
+    /// The header of the block (in front of `ptr`) is rewritten with the given length, capacity
+    /// and reference count.
+    ///
+    /// # Safety
+    ///
+    /// * The `ptr` must have been obtained by [`as_left_ptr`][CoWec::as_left_ptr] or
+    ///   [`as_left_mut_ptr`][CoWec::as_left_mut_ptr] of a `CoWec` of the same type that was then
+    ///   forgotten (eg. with [`mem::forget`]), and the block must not be reachable in any other
+    ///   way (unless `refcnt` accounts for the other handles).
+    /// * `cap` must be the [`capacity_left`][CoWec::capacity_left] of that `CoWec`.
+    /// * The first `len` elements must be initialized, `len <= cap`.
+    pub unsafe fn from_raw_parts_left(ptr: *mut T, len: usize, cap: usize, refcnt: R) -> Self {
+        debug_assert!(len <= cap);
+        let block = ptr
+            .cast::<u8>()
+            .sub(CoWecBlock::<R, T, L>::DATA_OFFSET)
+            .cast::<CoWecBlock<R, T, L>>();
+        // A tight block keeps its capacity stored in front of the header, which is left alone.
+        let cap_encoded = if cap >= 2 && cap.is_power_of_two() {
+            cap.trailing_zeros() as usize
+        } else {
+            debug_assert_eq!(CoWecBlock::tight_capacity(block), cap);
+            0
+        };
+        let header = CoWecBlock {
+            rcell: refcnt,
+            len: L::from_usize(len | (cap_encoded << CoWecBlock::<R, T, L>::CAP_OFFSET)),
+            data: [],
+        };
+        // The old header is just integers, nothing to drop.
+        ptr::write(block, header);
+        Self::from_left_block(block)
+    }
+
+    /// Returns a pointer to the first element of the left variant.
+    ///
+    /// The block is copied first if it is shared, so it is safe to write through the pointer (up
+    /// to the length). The pointer is valid until the `CoWec` is modified or dropped. Returns
+    /// `None` if this is not the left variant.
+    pub fn as_left_mut_ptr(&mut self) -> Option<*mut T>
+    where
+        T: Clone,
+    {
+        let block = self.make_mut_left()?;
+        Some(unsafe { CoWecBlock::get_data_mut(block).cast() })
+    }
+
+    /// Returns the element at `index` of the left variant.
+    ///
+    /// Returns `None` if this is not the left variant or if the index is out of bounds.
+    pub fn get_left(&self, index: usize) -> Option<&T> {
+        let block = self.left_block()?;
+        unsafe {
+            if index < (*block).len() {
+                Some(CoWecBlock::get(block, index))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the element at `index` of the left variant.
+    ///
+    /// If the block is shared with other clones, it is copied first (so the other clones don't see
+    /// the modification). Returns `None` if this is not the left variant or if the index is out
+    /// of bounds; no copy is made in such case.
+    pub fn get_left_mut(&mut self, index: usize) -> Option<&mut T>
+    where
+        T: Clone,
+    {
+        self.get_left(index)?;
+        let block = self.make_mut_left()?;
+        unsafe { Some(CoWecBlock::get_mut(block, index)) }
+    }
+
+    /// Replaces the element at `index` of the left variant, returning the old one.
+    ///
+    /// Unlike slice indexing, this doesn't panic. Returns `None` (dropping `val`) if this is not
+    /// the left variant or if the index is out of bounds; no copy is made in such case.
+    pub fn replace_left(&mut self, index: usize, val: T) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get_left_mut(index).map(|slot| mem::replace(slot, val))
+    }
+}
+
+/// Creates a left `CoWec`, moving the elements over.
+///
+/// An empty vector results in an empty left `CoWec` (not a stub).
+///
+/// # Panics
+///
+/// If the vector is longer than the maximum length of a block.
+impl<R, T, U, L, G> From<Vec<T>> for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn from(mut v: Vec<T>) -> Self {
+        let len = v.len();
+        let block = unsafe { CoWecBlock::<R, T, L>::create_for(len) };
+        unsafe {
+            ptr::copy_nonoverlapping(v.as_ptr(), CoWecBlock::get_data_mut(block).cast(), len);
+            // The elements are owned by the block now, the vector only frees its buffer.
+            v.set_len(0);
+            (*block).set_len(len);
+        }
+        Self::from_left_block(block)
+    }
+}
+
+/// Creates a left `CoWec`, moving the elements over.
+///
+/// An empty slice results in an empty left `CoWec` (not a stub).
+///
+/// # Panics
+///
+/// If the slice is longer than the maximum length of a block.
+impl<R, T, U, L, G> From<Box<[T]>> for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn from(b: Box<[T]>) -> Self {
+        let len = b.len();
+        let block = unsafe { CoWecBlock::<R, T, L>::create_for(len) };
+        unsafe {
+            let raw = Box::into_raw(b);
+            ptr::copy_nonoverlapping(raw as *const T, CoWecBlock::get_data_mut(block).cast(), len);
+            (*block).set_len(len);
+            // The elements are owned by the block now, free just the memory of the box.
+            drop(Box::from_raw(raw as *mut [MaybeUninit<T>]));
+        }
+        Self::from_left_block(block)
+    }
+}
+
+/// Creates a left `CoWec`, cloning the elements.
+///
+/// An empty slice results in an empty left `CoWec` (not a stub).
+///
+/// # Panics
+///
+/// If the slice is longer than the maximum length of a block.
+impl<R, T, U, L, G> From<&[T]> for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Clone,
+{
+    fn from(s: &[T]) -> Self {
+        let block = unsafe { CoWecBlock::<R, T, L>::create_for(s.len()) };
+        // Owned right away, so whatever was already cloned is dropped if a clone panics.
+        let me = Self::from_left_block(block);
+        for (i, elem) in s.iter().enumerate() {
+            unsafe { CoWecBlock::insert(block, i, elem.clone()) };
+        }
+        me
+    }
+}
+
+/// Extracts the elements of the left variant.
+///
+/// The elements are moved out if the block is not shared, cloned otherwise. The other variants
+/// result in an empty vector.
+impl<R, T, U, L, G> From<CoWec<R, T, U, L, G>> for Vec<T>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Clone,
+{
+    fn from(mut c: CoWec<R, T, U, L, G>) -> Self {
+        let block = match c.make_mut_left() {
+            Some(block) => block,
+            None => return Vec::new(),
+        };
+        unsafe {
+            let len = (*block).len();
+            let mut v = Vec::with_capacity(len);
+            ptr::copy_nonoverlapping(CoWecBlock::get_data(block).cast(), v.as_mut_ptr(), len);
+            // Moved to the vector, the block is left with nothing to drop.
+            (*block).set_len(0);
+            v.set_len(len);
+            v
+        }
+    }
+}
+
+// Same as with Arc ‒ the elements are shared (so must be Sync) and may be dropped in another
+// thread (so must be Send).
+unsafe impl<T, U, L, G> Send for CoWec<AtomicRCell, T, U, L, G>
+where
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Send + Sync,
+    U: Send + Sync,
+{
+}
+
+unsafe impl<T, U, L, G> Sync for CoWec<AtomicRCell, T, U, L, G>
+where
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Send + Sync,
+    U: Send + Sync,
+{
+}
+
+impl<R, T, U, L, G> Clone for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    // FIXME: But we may want to provide shrinking and it would be great if we could do it when
+    // getting shared.
+    fn clone(&self) -> Self {
+        let ptr = if self.is_left() {
+            unsafe { CoWecBlock::<R, T, L>::inc_ref(self.ptr as *mut _) as usize }
+        } else if self.is_right() {
+            unsafe { CoWecBlock::<R, U, L>::inc_ref((self.ptr - 1) as *mut _) as usize + 1 }
+        } else {
+            0
+        };
+        Self {
+            ptr,
+            _l: PhantomData,
+            _r: PhantomData,
+            _g: PhantomData,
+        }
+    }
+}
+
+/// The default is the stub (which doesn't allocate).
+impl<R, T, U, L, G> Default for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn default() -> Self {
+        Self::new_stub()
+    }
+}
+
+/// Two `CoWec`s are equal if they hold the same variant with equal elements.
+impl<R, T, U, L, G> PartialEq for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: PartialEq,
+    U: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self.left_slice(), other.left_slice()) {
+            (Some(l), Some(r)) => return l == r,
+            (None, None) => (),
+            _ => return false,
+        }
+        match (self.right_slice(), other.right_slice()) {
+            (Some(l), Some(r)) => l == r,
+            (None, None) => true, // Both stubs
+            _ => false,
+        }
+    }
+}
+
+impl<R, T, U, L, G> Eq for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Eq,
+    U: Eq,
+{
+}
+
+/// Orders the stub first, then the left variants and the right ones last.
+///
+/// `CoWec`s of the same variant are compared lexicographically by their elements, the same way as
+/// slices are.
+impl<R, T, U, L, G> PartialOrd for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: PartialOrd,
+    U: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.variant_rank().cmp(&other.variant_rank()) {
+            Ordering::Equal => (),
+            ord => return Some(ord),
+        }
+        if let (Some(l), Some(r)) = (self.left_slice(), other.left_slice()) {
+            l.partial_cmp(r)
+        } else if let (Some(l), Some(r)) = (self.right_slice(), other.right_slice()) {
+            l.partial_cmp(r)
+        } else {
+            Some(Ordering::Equal)
+        }
+    }
+}
+
+impl<R, T, U, L, G> Ord for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Ord,
+    U: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.variant_rank()
+            .cmp(&other.variant_rank())
+            .then_with(|| match (self.left_slice(), other.left_slice()) {
+                (Some(l), Some(r)) => l.cmp(r),
+                _ => self.right_slice().cmp(&other.right_slice()),
+            })
+    }
+}
+
+/// Hashes the content.
+///
+/// The left variant hashes the same as the equivalent `[T]` slice (so it can be eventually looked
+/// up by one). The other variants are prefixed by a tag, to tell them apart.
+impl<R, T, U, L, G> Hash for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Hash,
+    U: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Some(left) = self.left_slice() {
+            left.hash(state);
+        } else if let Some(right) = self.right_slice() {
+            state.write_u8(2);
+            right.hash(state);
+        } else {
+            state.write_u8(0);
+        }
+    }
+}
+
+/// Writes the elements of a slice separated by commas, in brackets.
+fn display_slice<E: Display>(elems: &[E], fmt: &mut Formatter) -> FmtResult {
+    fmt.write_str("[")?;
+    for (i, elem) in elems.iter().enumerate() {
+        if i > 0 {
+            fmt.write_str(", ")?;
+        }
+        elem.fmt(fmt)?;
+    }
+    fmt.write_str("]")
+}
+
+/// Formats the left variant as `[a, b, c]`, the right one as `[a, b, c]R` and the stub as `()`.
+impl<R, T, U, L, G> Display for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+    T: Display,
+    U: Display,
+{
+    fn fmt(&self, fmt: &mut Formatter) -> FmtResult {
+        if let Some(left) = self.left_slice() {
+            display_slice(left, fmt)
+        } else if let Some(right) = self.right_slice() {
+            display_slice(right, fmt)?;
+            fmt.write_str("R")
+        } else {
+            fmt.write_str("()")
+        }
+    }
+}
+
+impl<R, T, U, L, G> Drop for CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    fn drop(&mut self) {
+        if self.is_left() {
+            unsafe { CoWecBlock::<R, T, L>::dec_ref(self.ptr as *mut _) }
+        } else if self.is_right() {
+            unsafe { CoWecBlock::<R, U, L>::dec_ref((self.ptr - 1) as *mut _) }
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::prelude::v1::*;
+    use std::rc::Rc;
+    use std::vec;
+
     use super::*;
+    use crate::model::{Model, Op};
+
+    type B = CoWecBlock::<RCell, String>;
+
+    /// Test some allocation routines (create/resize/dispose).
+    ///
+    /// Aimed for valgrind and/or miri testing, mostly, to see if we are not doing ugly things in
+    /// there.
+    #[test]
+    fn allocation() {
+        unsafe {
+            let mut me = B::create(4);
+            let mut me_ref = &*me;
+            assert_eq!(me_ref.len(), 0);
+            assert_eq!(B::capacity(me), 4);
+            me = B::resize(me, 8);
+            me_ref = &*me;
+            assert_eq!(me_ref.len(), 0);
+            assert_eq!(B::capacity(me), 8);
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn insert_end() {
+        unsafe {
+            let me = B::create(4);
+            B::insert(me, 0, "Hello".to_owned());
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 1);
+            assert_eq!(B::capacity(me), 4);
+            assert_eq!(B::get(me, 0), "Hello");
+            B::insert(me, 1, "World".to_owned());
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 2);
+            assert_eq!(B::capacity(me), 4);
+            assert_eq!(B::get(me, 0), "Hello");
+            assert_eq!(B::get(me, 1), "World");
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn move_to() {
+        unsafe {
+            let src = B::create(2);
+            B::insert(src, 0, "a".to_owned());
+            B::insert(src, 1, "b".to_owned());
+            let dst = B::create(4);
+            B::insert(dst, 0, "x".to_owned());
+            B::move_to(src, dst);
+            assert_eq!((*src).len(), 0);
+            assert_eq!(B::capacity(src), 2);
+            assert_eq!((*dst).len(), 3);
+            assert_eq!(B::as_slice(dst), ["x", "a", "b"]);
+            // Nothing left to move
+            B::move_to(src, dst);
+            assert_eq!((*dst).len(), 3);
+            B::dispose(src);
+            B::dispose(dst);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity 1 can't be encoded")]
+    fn create_one() {
+        unsafe { B::create(1) };
+    }
+
+    #[test]
+    fn push() {
+        unsafe {
+            let mut me = B::create(2);
+            for i in 0..9 {
+                B::push::<DoublingGrowth>(&mut me, i.to_string());
+            }
+            let me_ref = &*me;
+            assert_eq!(me_ref.len(), 9);
+            assert_eq!(B::capacity(me), 16);
+            assert_eq!(B::get(me, 0), "0");
+            assert_eq!(B::get(me, 8), "8");
+            me = B::shrink_to(me, 0);
+            assert!(B::is_tight(me));
+            // Double of 9, rounded up
+            B::push::<DoublingGrowth>(&mut me, "9".to_owned());
+            assert_eq!(B::capacity(me), 32);
+            assert_eq!(B::get(me, 9), "9");
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn tight() {
+        unsafe {
+            let mut me = B::create(8);
+            for i in 0..7 {
+                B::push::<DoublingGrowth>(&mut me, i.to_string());
+            }
+            me = B::shrink_to(me, 0);
+            assert!(B::is_tight(me));
+            assert_eq!(B::capacity(me), 7);
+            // The capacity stays, no matter the length
+            assert_eq!(B::remove(me, 6), "6");
+            assert_eq!(B::remove(me, 0), "0");
+            assert_eq!((*me).len(), 5);
+            assert_eq!(B::capacity(me), 7);
+            B::push::<DoublingGrowth>(&mut me, "7".to_owned());
+            B::push::<DoublingGrowth>(&mut me, "8".to_owned());
+            assert!(B::is_tight(me));
+            assert_eq!(B::capacity(me), 7);
+            // Shrinking a tight block keeps it tight
+            assert_eq!(B::remove(me, 0), "1");
+            me = B::shrink_to(me, 0);
+            assert!(B::is_tight(me));
+            assert_eq!(B::capacity(me), 6);
+            B::clear(me);
+            assert_eq!(B::capacity(me), 6);
+            me = B::shrink_to(me, 0);
+            assert_eq!(B::capacity(me), 0);
+            B::push::<DoublingGrowth>(&mut me, "a".to_owned());
+            assert!(!B::is_tight(me));
+            assert_eq!(B::capacity(me), 2);
+            assert_eq!(B::get(me, 0), "a");
+            B::dispose(me);
+
+            // Disposed together with its elements, with the right layout
+            let mut me = B::create(4);
+            for i in 0..3 {
+                B::push::<DoublingGrowth>(&mut me, i.to_string());
+            }
+            me = B::shrink_to(me, 0);
+            assert_eq!(B::remove(me, 0), "0");
+            B::dispose(me);
+        }
+    }
+
+    /// Growing a tight block keeps what's stored above the length (splicing relies on that).
+    #[test]
+    fn tight_grow() {
+        type C = CoWec<RCell, u64, ()>;
+        let mut c = C::from((0..7).collect::<Vec<_>>());
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(7));
+        c.splice_left(1..2, vec![100, 101, 102]);
+        assert_eq!(c.left_slice(), Some(&[0, 100, 101, 102, 2, 3, 4, 5, 6][..]));
+
+        let mut c = left_from(&["a", "b", "c"]);
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(3));
+        c.splice_left(1..2, vec!["x".to_owned(), "y".to_owned()]);
+        assert_left(&c, &["a", "x", "y", "c"]);
+        c.shrink_to_fit_left();
+        c.insert_left(0, "z".to_owned()).unwrap();
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(5));
+        c.insert_left(2, "w".to_owned()).unwrap();
+        assert_left(&c, &["z", "a", "w", "x", "y", "c"]);
+        c.reserve_left_exact(3);
+        c.insert_left(6, "v".to_owned()).unwrap();
+        assert_left(&c, &["z", "a", "w", "x", "y", "c", "v"]);
+    }
+
+    #[test]
+    fn growth_policies() {
+        unsafe {
+            let mut me = B::create(2);
+            for i in 0..9 {
+                B::push::<DoublingGrowth>(&mut me, i.to_string());
+            }
+            me = B::shrink_to(me, 0);
+            B::push::<ExactGrowth>(&mut me, "9".to_owned());
+            assert_eq!(B::capacity(me), 16);
+            B::dispose(me);
+
+            let mut me = B::create(2);
+            for i in 0..3 {
+                B::push::<LinearGrowth<100>>(&mut me, i.to_string());
+            }
+            assert_eq!(B::capacity(me), 128);
+            B::dispose(me);
+        }
+
+        // Never over what a block can hold
+        let mut c = CoWec::<RCell, u8, (), u16, LinearGrowth<10_000>>::from(&[1, 2][..]);
+        c.push_left(3).unwrap();
+        assert_eq!(c.capacity_left(), Some(4096));
+        c.reserve_left(4092);
+        assert_eq!(
+            c.try_reserve_left(4093).unwrap_err(),
+            TryReserveError::CapacityOverflow(CapacityError {
+                requested: 4096,
+                limit: 4095,
+            })
+        );
+    }
+
+    #[test]
+    fn insert_beginning() {
+        unsafe {
+            let me = B::create(4);
+            B::insert(me, 0, "Hello".to_owned());
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 1);
+            assert_eq!(B::capacity(me), 4);
+            assert_eq!(B::get(me, 0), "Hello");
+            B::insert(me, 0, "World".to_owned());
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 2);
+            assert_eq!(B::capacity(me), 4);
+            assert_eq!(B::get(me, 0), "World");
+            assert_eq!(B::get(me, 1), "Hello");
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn replace() {
+        unsafe {
+            let me = B::create(4);
+            B::insert(me, 0, "Hello".to_owned());
+            *B::get_mut(me, 0) = "World".to_owned();
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 1);
+            assert_eq!(B::capacity(me), 4);
+            assert_eq!(B::get(me, 0), "World");
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn remove() {
+        unsafe {
+            let me = B::create(4);
+            B::insert(me, 0, "Hello".to_owned());
+            B::insert(me, 1, "World".to_owned());
+            assert_eq!(B::remove(me, 0), "Hello");
+            assert_eq!(B::remove(me, 0), "World");
+            let me_ref = &mut *me;
+            assert_eq!(me_ref.len(), 0);
+            assert_eq!(B::capacity(me), 4);
+            B::dispose(me);
+        }
+    }
+
+    #[test]
+    fn clone_block() {
+        unsafe {
+            let me = B::create(4);
+            B::insert(me, 0, "Hello".to_owned());
+            B::insert(me, 1, "World".to_owned());
+            let copy = B::clone_block(me);
+            assert_ne!(me, copy);
+            assert_eq!((*copy).len(), 2);
+            assert_eq!(B::capacity(copy), 4);
+            assert!((*copy).rcell.is_unique());
+            assert_eq!(B::get(copy, 0), "Hello");
+            assert_eq!(B::get(copy, 1), "World");
+            B::dispose(me);
+            B::dispose(copy);
+        }
+    }
+
+    /// Counts the living instances and panics on a set clone.
+    struct Bomb {
+        live: Rc<Cell<usize>>,
+        clones_left: Rc<Cell<usize>>,
+    }
+
+    impl Clone for Bomb {
+        fn clone(&self) -> Self {
+            let left = self.clones_left.get();
+            if left == 0 {
+                std::panic!("Boom");
+            }
+            self.clones_left.set(left - 1);
+            self.live.set(self.live.get() + 1);
+            Self {
+                live: Rc::clone(&self.live),
+                clones_left: Rc::clone(&self.clones_left),
+            }
+        }
+    }
+
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            self.live.set(self.live.get() - 1);
+        }
+    }
+
+    fn bombs(live: &Rc<Cell<usize>>, clones_left: &Rc<Cell<usize>>, n: usize) -> CoWecRc<Bomb, ()> {
+        let mut c = CoWec::new_left();
+        for _ in 0..n {
+            live.set(live.get() + 1);
+            let bomb = Bomb {
+                live: Rc::clone(live),
+                clones_left: Rc::clone(clones_left),
+            };
+            c.push_left(bomb).unwrap();
+        }
+        c
+    }
+
+    /// A panic while constructing the inserted value leaves the content intact.
+    #[test]
+    fn insert_panic_constructor() {
+        let live = Rc::new(Cell::new(0));
+        let clones_left = Rc::new(Cell::new(0));
+        let mut c = bombs(&live, &clones_left, 3);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            // The constructor clones, but no clones are allowed
+            let val = c.get_left(0).unwrap().clone();
+            c.insert_left(1, val).unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(c.len_left(), Some(3));
+        assert_eq!(live.get(), 3);
+        drop(c);
+        assert_eq!(live.get(), 0);
+    }
+
+    /// A panic while copying the shared block during insertion drops the value and leaves both
+    /// handles intact.
+    #[test]
+    fn insert_panic_copy() {
+        let live = Rc::new(Cell::new(0));
+        let clones_left = Rc::new(Cell::new(0));
+        let mut c = bombs(&live, &clones_left, 4);
+        let shared = c.clone();
+        let mut extra = bombs(&live, &clones_left, 1);
+        let val = extra.pop_left().unwrap();
+        clones_left.set(2);
+        let result = catch_unwind(AssertUnwindSafe(|| c.insert_left(2, val)));
+        assert!(result.is_err());
+        assert_eq!(live.get(), 4);
+        assert_eq!(c.ptr, shared.ptr);
+        assert_eq!(c.len_left(), Some(4));
+        drop(c);
+        drop(shared);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn clone_block_panic() {
+        let live = Rc::new(Cell::new(0));
+        let clones_left = Rc::new(Cell::new(0));
+        unsafe {
+            let me = CoWecBlock::<RCell, Bomb>::create(8);
+            for i in 0..5 {
+                live.set(live.get() + 1);
+                let bomb = Bomb {
+                    live: Rc::clone(&live),
+                    clones_left: Rc::clone(&clones_left),
+                };
+                CoWecBlock::insert(me, i, bomb);
+            }
+            clones_left.set(3);
+            let result = catch_unwind(AssertUnwindSafe(|| CoWecBlock::clone_block(me)));
+            assert!(result.is_err());
+            // The 3 successful clones got dropped again
+            assert_eq!(live.get(), 5);
+            CoWecBlock::dispose(me);
+            assert_eq!(live.get(), 0);
+        }
+    }
+
+    type CW = CoWec::<RCell, String, usize>;
+
+    /// Check construction & destruction of the empty thing
+    #[test]
+    fn create_empty() {
+        let c = CW::new_stub();
+        assert!(c.is_stub());
+        assert!(!c.is_left());
+        assert!(!c.is_right());
+    }
+
+    #[test]
+    fn create_left() {
+        let c = CW::new_left();
+        assert!(!c.is_stub());
+        assert!(c.is_left());
+        assert!(!c.is_right());
+    }
+
+    #[test]
+    fn create_right() {
+        let c = CW::new_right();
+        assert!(!c.is_stub());
+        assert!(!c.is_left());
+        assert!(c.is_right());
+    }
+
+    #[test]
+    #[allow(clippy::redundant_clone)]
+    fn clone_stub() {
+        let c = CW::new_stub();
+        let _d = c.clone();
+    }
+
+    #[test]
+    #[allow(clippy::redundant_clone)]
+    fn clone_left_empty() {
+        let c = CW::new_left();
+        let _d = c.clone();
+    }
+
+    fn left_from(items: &[&str]) -> CW {
+        let block = unsafe { B::create(items.len().next_power_of_two().max(2)) };
+        for (i, item) in items.iter().enumerate() {
+            unsafe { B::insert(block, i, (*item).to_owned()) };
+        }
+        CW::from_left_block(block)
+    }
+
+    fn left_hello_world() -> CW {
+        let c = CW::new_left();
+        unsafe {
+            let block = c.ptr as *mut B;
+            B::insert(block, 0, "Hello".to_owned());
+            B::insert(block, 1, "World".to_owned());
+        }
+        c
+    }
+
+    #[test]
+    fn get_left() {
+        let c = left_hello_world();
+        assert_eq!(c.get_left(0).unwrap(), "Hello");
+        assert_eq!(c.get_left(1).unwrap(), "World");
+        assert!(c.get_left(2).is_none());
+    }
+
+    #[test]
+    fn get_left_other_variants() {
+        assert!(CW::new_stub().get_left(0).is_none());
+        assert!(CW::new_right().get_left(0).is_none());
+        assert!(CW::new_left().get_left(0).is_none());
+    }
+
+    #[test]
+    fn get_left_mut() {
+        let mut c = left_hello_world();
+        *c.get_left_mut(0).unwrap() = "Bye".to_owned();
+        c.get_left_mut(1).unwrap().push('!');
+        assert!(c.get_left_mut(2).is_none());
+        assert_eq!(c.get_left(0).unwrap(), "Bye");
+        assert_eq!(c.get_left(1).unwrap(), "World!");
+        assert!(CW::new_stub().get_left_mut(0).is_none());
+        assert!(CW::new_right().get_left_mut(0).is_none());
+    }
+
+    /// Modifying a shared block makes a private copy, the other clone stays intact.
+    #[test]
+    fn get_left_mut_shared() {
+        let c = left_hello_world();
+        let mut d = c.clone();
+        *d.get_left_mut(0).unwrap() = "Bye".to_owned();
+        assert_eq!(c.get_left(0).unwrap(), "Hello");
+        assert_eq!(d.get_left(0).unwrap(), "Bye");
+        assert_eq!(c.get_left(1).unwrap(), "World");
+        assert_eq!(d.get_left(1).unwrap(), "World");
+        assert_ne!(c.ptr, d.ptr);
+    }
+
+    fn assert_left(c: &CW, expected: &[&str]) {
+        for (i, e) in expected.iter().enumerate() {
+            assert_eq!(c.get_left(i).unwrap(), e);
+        }
+        assert!(c.get_left(expected.len()).is_none());
+    }
+
+    #[test]
+    fn coalesce_left() {
+        let mut c = left_from(&["a", "b"]);
+        let mut d = left_from(&["c", "d", "e"]);
+        assert!(c.coalesce_left(&mut d));
+        assert_left(&c, &["a", "b", "c", "d", "e"]);
+        assert_left(&d, &[]);
+    }
+
+    #[test]
+    fn coalesce_left_shared() {
+        let mut c = left_from(&["a"]);
+        let c2 = c.clone();
+        let mut d = left_from(&["b", "c"]);
+        let d2 = d.clone();
+        assert!(c.coalesce_left(&mut d));
+        assert_left(&c, &["a", "b", "c"]);
+        assert_left(&d, &[]);
+        assert_left(&c2, &["a"]);
+        assert_left(&d2, &["b", "c"]);
+        // Coalescing with a clone of itself
+        let mut e = c2.clone();
+        assert!(e.coalesce_left(&mut c2.clone()));
+        assert_left(&e, &["a", "a"]);
+    }
+
+    #[test]
+    fn coalesce_left_other_variants() {
+        let mut c = left_from(&["a"]);
+        assert!(!c.coalesce_left(&mut CW::new_right()));
+        assert!(!c.coalesce_left(&mut CW::new_stub()));
+        assert!(!CW::new_stub().coalesce_left(&mut c));
+        assert_left(&c, &["a"]);
+    }
+
+    #[test]
+    fn insert_remove_left() {
+        let mut c = CW::new_left();
+        c.insert_left(0, "b".to_owned()).unwrap();
+        c.insert_left(0, "a".to_owned()).unwrap();
+        // Over the initial capacity of 2
+        c.insert_left(2, "d".to_owned()).unwrap();
+        c.insert_left(2, "c".to_owned()).unwrap();
+        c.push_left("e".to_owned()).unwrap();
+        assert_left(&c, &["a", "b", "c", "d", "e"]);
+        assert_eq!(c.remove_left(1), "b");
+        assert_eq!(c.pop_left().unwrap(), "e");
+        assert_eq!(c.remove_left(0), "a");
+        assert_left(&c, &["c", "d"]);
+        assert_eq!(c.pop_left().unwrap(), "d");
+        assert_eq!(c.pop_left().unwrap(), "c");
+        assert!(c.pop_left().is_none());
+    }
+
+    #[test]
+    fn insert_remove_left_shared() {
+        let mut c = left_from(&["a", "b"]);
+        let d = c.clone();
+        c.push_left("c".to_owned()).unwrap();
+        assert_left(&d, &["a", "b"]);
+        let e = c.clone();
+        assert_eq!(c.remove_left(0), "a");
+        assert_left(&c, &["b", "c"]);
+        assert_left(&d, &["a", "b"]);
+        assert_left(&e, &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn insert_left_wrong_variant() {
+        let mut c = CW::new_right();
+        assert_eq!(
+            c.insert_left(0, "a".to_owned()),
+            Err(InsertError::WrongVariant)
+        );
+        assert_eq!(c.push_left("a".to_owned()), Err(InsertError::WrongVariant));
+        assert!(c.pop_left().is_none());
+        let mut c = CW::new_stub();
+        assert_eq!(c.push_left("a".to_owned()), Err(InsertError::WrongVariant));
+    }
+
+    #[test]
+    fn insert_left_full() {
+        let mut c = CoWec::<RCell, u8, ()>::new_left();
+        for _ in 0..4095 {
+            c.push_left(0).unwrap();
+        }
+        assert_eq!(c.push_left(0), Err(InsertError::Full));
+        assert_eq!(c.len_left(), Some(4095));
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 1)")]
+    fn insert_left_out_of_bounds() {
+        let mut c = left_from(&["a"]);
+        let _ = c.insert_left(2, "b".to_owned());
+    }
+
+    #[test]
+    #[should_panic(expected = "removal index (is 1) should be < len (is 1)")]
+    fn remove_left_out_of_bounds() {
+        let mut c = left_from(&["a"]);
+        c.remove_left(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "not holding the left variant")]
+    fn remove_left_wrong_variant() {
+        CW::new_right().remove_left(0);
+    }
+
+    fn hash_of<H: Hash + ?Sized>(h: &H) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+
+        let mut hasher = DefaultHasher::new();
+        h.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn hash() {
+        let c = left_from(&["a", "b"]);
+        let d = left_from(&["a", "b"]);
+        assert_eq!(hash_of(&c), hash_of(&d));
+        assert_eq!(hash_of(&c), hash_of(&c.clone()));
+        let v = vec!["a".to_owned(), "b".to_owned()];
+        assert_eq!(hash_of(&c), hash_of(&v));
+        assert_eq!(hash_of(&c), hash_of(&v[..]));
+        assert_ne!(hash_of(&c), hash_of(&left_from(&["a"])));
+        assert_eq!(hash_of(&CW::new_stub()), hash_of(&CW::new_stub()));
+        assert_eq!(hash_of(&CW::new_right()), hash_of(&CW::new_right()));
+        assert_ne!(hash_of(&CW::new_left()), hash_of(&CW::new_right()));
+    }
+
+    /// Check the bulk-hashed types (like integers) are consistent with slices too.
+    #[test]
+    fn hash_bytes() {
+        let mut c = CoWec::<RCell, u8, ()>::new_left();
+        for i in 0..10 {
+            c.push_left(i).unwrap();
+        }
+        let v = (0..10).collect::<Vec<u8>>();
+        assert_eq!(hash_of(&c), hash_of(&v));
+    }
+
+    #[test]
+    fn as_transmuted_right() {
+        let c = left_from(&["a"]);
+        let t = c.as_transmuted_right::<f32>().unwrap();
+        assert!(t.is_left());
+        assert_eq!(t.get_left(0).unwrap(), "a");
+        // Owned clones are fine too, they share the block
+        let d: CoWec<RCell, String, f32> = t.clone();
+        drop(c);
+        assert_eq!(d.get_left(0).unwrap(), "a");
+        let stub = CW::new_stub();
+        assert!(stub.as_transmuted_right::<f32>().unwrap().is_stub());
+        assert!(CW::new_right().as_transmuted_right::<f32>().is_none());
+    }
+
+    #[test]
+    fn eq() {
+        assert!(left_from(&["a", "b"]) == left_from(&["a", "b"]));
+        assert!(left_from(&["a", "b"]) != left_from(&["a"]));
+        assert!(CW::new_left() != CW::new_right());
+        assert!(CW::new_left() != CW::new_stub());
+        assert!(CW::new_stub() == CW::new_stub());
+        assert!(CW::new_right() == CW::new_right());
+    }
+
+    #[test]
+    fn cmp_variants() {
+        let stub = CW::new_stub();
+        let left = left_from(&["z", "z"]);
+        let right = CW::new_right();
+        assert!(stub < left);
+        assert!(left < right);
+        assert!(stub < right);
+        assert_eq!(stub.cmp(&CW::new_stub()), Ordering::Equal);
+        assert_eq!(right.cmp(&CW::new_right()), Ordering::Equal);
+    }
 
-    type B = CoWecBlock::<RCell, String>;
+    /// A tiny deterministic PRNG (xorshift), so the random tests are reproducible.
+    struct XorShift(u64);
 
-    /// Test some allocation routines (create/resize/dispose).
-    ///
-    /// Aimed for valgrind and/or miri testing, mostly, to see if we are not doing ugly things in
-    /// there.
+    impl XorShift {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Random sequences of operations over several handles, checked against vectors.
     #[test]
-    fn allocation() {
+    fn random_ops_model() {
+        for seed in 1..=50 {
+            let mut rng = XorShift(seed);
+            let mut model = Model::new();
+            for _ in 0..300 {
+                let h = rng.below(model.handles());
+                let len = model.len(h);
+                let op = match rng.below(6) {
+                    // Insert, biased towards the edges (positions 0 and len)
+                    0 | 1 => {
+                        let pos = match rng.below(3) {
+                            0 => 0,
+                            1 => len,
+                            _ => rng.below(len + 1),
+                        };
+                        Op::InsertAt(pos, rng.below(1000) as u32)
+                    }
+                    2 if rng.below(2) == 0 => Op::RemoveAt(0),
+                    2 => Op::RemoveAt(rng.below(len.max(1))),
+                    3 => Op::GetAt(rng.below(len + 1)),
+                    4 => Op::Clone,
+                    _ => Op::DropClone,
+                };
+                model.apply(h, op);
+            }
+        }
+    }
+
+    /// All the sequences of up to `len` elements of `0..3`.
+    fn sequences(len: usize) -> Vec<Vec<u8>> {
+        let mut result = vec![vec![]];
+        let mut last = vec![vec![]];
+        for _ in 0..len {
+            last = last
+                .iter()
+                .flat_map(|s| {
+                    (0..3).map(move |i| {
+                        let mut s = s.clone();
+                        s.push(i);
+                        s
+                    })
+                })
+                .collect();
+            result.extend(last.iter().cloned());
+        }
+        result
+    }
+
+    /// The ordering of the same variants is consistent with ordering of vectors.
+    #[test]
+    fn cmp_as_vec() {
+        type C = CoWec<RCell, u8, u8>;
+        let to_left = |v: &[u8]| {
+            let mut c = C::new_left();
+            for i in v {
+                c.push_left(*i).unwrap();
+            }
+            c
+        };
+        let seqs = sequences(3);
+        for a in &seqs {
+            for b in &seqs {
+                let (ca, cb) = (to_left(a), to_left(b));
+                assert_eq!(ca.cmp(&cb), a.cmp(b), "{:?} vs {:?}", a, b);
+                assert_eq!(ca.partial_cmp(&cb), a.partial_cmp(b));
+                assert_eq!(ca == cb, a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn atomic() {
+        use crate::prelude::*;
+
+        let mut c = CoWecArc::<String, ()>::new_left();
+        c.push_left("a".to_owned()).unwrap();
+        let mut d = c.clone();
+        d.push_left("b".to_owned()).unwrap();
+        assert_eq!(c.len_left(), Some(1));
+        assert_eq!(d.len_left(), Some(2));
+        let e: CoWecRc<String, ()> = CoWec::new_left();
+        assert!(e.is_left());
+    }
+
+    #[test]
+    fn atomic_rcell() {
+        let cnt = AtomicRCell::default();
+        assert!(cnt.is_unique());
+        assert!(cnt.inc_ref());
+        assert!(!cnt.is_unique());
+        assert!(!cnt.dec_ref());
+        assert!(cnt.is_unique());
+        cnt.0.store(u16::MAX, AtomicOrdering::Relaxed);
+        assert!(!cnt.inc_ref());
+        assert_eq!(cnt.0.load(AtomicOrdering::Relaxed), u16::MAX);
+        cnt.0.store(1, AtomicOrdering::Relaxed);
+        assert!(cnt.dec_ref());
+    }
+
+    #[test]
+    fn try_clone_left() {
+        let c = left_from(&["a"]);
+        let d = c.try_clone_left().unwrap();
+        assert_eq!(c.ptr, d.ptr);
+        let block = c.left_block().unwrap();
         unsafe {
-            let mut me = B::create(4);
-            let mut me_ref = &*me;
-            assert_eq!(me_ref.len(), 0);
-            assert_eq!(me_ref.capacity(), 4);
-            me = B::resize(me, 8);
-            me_ref = &*me;
-            assert_eq!(me_ref.len(), 0);
-            assert_eq!(me_ref.capacity(), 8);
-            B::dispose(me);
+            let rcell = &(*block).rcell;
+            let old = rcell.0.replace(u16::MAX);
+            assert!(c.try_clone_left().is_none());
+            rcell.0.set(old);
         }
+        assert!(CW::new_stub().try_clone_left().is_none());
+        assert!(CW::new_right().try_clone_left().is_none());
     }
 
     #[test]
-    fn insert_end() {
+    fn deep_clone_left() {
+        let c = left_hello_world();
+        let _shared = c.clone();
+        let d = c.deep_clone_left().unwrap();
+        assert_ne!(c.ptr, d.ptr);
+        assert!(unsafe { (*d.left_block().unwrap()).rcell.is_unique() });
+        assert!(!unsafe { (*c.left_block().unwrap()).rcell.is_unique() });
+        assert!(c == d);
+        assert!(CW::new_stub().deep_clone_left().is_none());
+        assert!(CW::new_right().deep_clone_left().is_none());
+    }
+
+    #[test]
+    fn leak_left() {
+        let mut c = left_hello_world();
+        let block = c.left_block().unwrap();
+        let leaked = c.clone().leak_left().unwrap();
+        assert_eq!(leaked, ["Hello", "World"]);
+        assert_eq!(
+            leaked.as_ptr(),
+            unsafe { CoWecBlock::get_data(block) }.cast()
+        );
+        // The other handle has to copy now, the leaked reference counts
+        c.push_left("!".to_owned()).unwrap();
+        assert_ne!(c.left_block().unwrap(), block);
+        assert_eq!(leaked, ["Hello", "World"]);
+        assert!(CW::new_stub().leak_left().is_none());
+        assert!(CW::new_right().leak_left().is_none());
+        // Reclaim it after all, to keep leak checkers quiet
+        unsafe { B::dec_ref(block) };
+    }
+
+    #[test]
+    fn raw_parts_left() {
+        type C = CoWec<RCell, u32, ()>;
+        let mut c = C::from(vec![1, 2, 3]);
+        let shared = c.clone();
+        assert_eq!(c.as_left_ptr(), shared.as_left_ptr());
+        assert_eq!(unsafe { *c.as_left_ptr().unwrap().add(2) }, 3);
+        // Unshares it first
+        let ptr = c.as_left_mut_ptr().unwrap();
+        assert_ne!(Some(ptr.cast_const()), shared.as_left_ptr());
+        let cap = c.capacity_left().unwrap();
+        mem::forget(c);
+        unsafe { *ptr = 10 };
+        let c = unsafe { C::from_raw_parts_left(ptr, 2, cap, RCell::default()) };
+        assert_eq!(c.left_slice(), Some(&[10, 2][..]));
+        assert_eq!(c.capacity_left(), Some(4));
+        assert!(C::new_right().as_left_ptr().is_none());
+    }
+
+    #[test]
+    fn aliases() {
+        use crate::prelude::*;
+
+        let mut single = SingleCoWec::<RCell, u32>::new_left();
+        single.push_left(1).unwrap();
+        assert_eq!(single.len(), 1);
+        let bytes = ByteCoWec::<AtomicRCell, ()>::from(&b"ab"[..]);
+        assert_eq!(bytes.to_hex_left(), "6162");
+    }
+
+    #[test]
+    fn atomic_threads() {
+        let mut c = CoWecArc::<String, usize>::new_left();
+        c.push_left("a".to_owned()).unwrap();
+        let threads = (0..4)
+            .map(|i| {
+                let mut c = c.clone();
+                std::thread::spawn(move || {
+                    assert_eq!(c.get_left(0).unwrap(), "a");
+                    c.push_left(i.to_string()).unwrap();
+                    c
+                })
+            })
+            .collect::<Vec<_>>();
+        for (i, t) in threads.into_iter().enumerate() {
+            let t = t.join().unwrap();
+            assert_eq!(t.get_left(1).unwrap(), &i.to_string());
+        }
+        assert_eq!(c.len_left(), Some(1));
+        assert!(unsafe { (*c.left_block().unwrap()).rcell.is_unique() });
+    }
+
+    #[test]
+    fn with_default_left() {
+        let c = CW::with_default_left(5);
+        assert_left(&c, &["", "", "", "", ""]);
         unsafe {
-            let me = B::create(4);
-            B::insert(me, 0, "Hello".to_owned());
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 1);
-            assert_eq!(me_ref.capacity(), 4);
-            assert_eq!(B::get(me, 0), "Hello");
-            B::insert(me, 1, "World".to_owned());
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 2);
-            assert_eq!(me_ref.capacity(), 4);
-            assert_eq!(B::get(me, 0), "Hello");
-            assert_eq!(B::get(me, 1), "World");
-            B::dispose(me);
+            assert_eq!(CoWecBlock::capacity(c.left_block().unwrap()), 8);
         }
+        let c = CW::with_default_left(0);
+        assert_left(&c, &[]);
     }
 
     #[test]
-    fn insert_beginning() {
+    fn with_zeros_left() {
+        type C = CoWec<RCell, u32, ()>;
+        let c = unsafe { C::with_zeros_left(5) };
+        assert_eq!(c.left_slice(), Some(&[0; 5][..]));
         unsafe {
-            let me = B::create(4);
-            B::insert(me, 0, "Hello".to_owned());
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 1);
-            assert_eq!(me_ref.capacity(), 4);
-            assert_eq!(B::get(me, 0), "Hello");
-            B::insert(me, 0, "World".to_owned());
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 2);
-            assert_eq!(me_ref.capacity(), 4);
-            assert_eq!(B::get(me, 0), "World");
-            assert_eq!(B::get(me, 1), "Hello");
-            B::dispose(me);
+            assert_eq!(CoWecBlock::capacity(c.left_block().unwrap()), 8);
         }
+        let c = unsafe { C::with_zeros_left(0) };
+        assert_eq!(c.left_slice(), Some(&[][..]));
+        let c = unsafe { CoWec::<RCell, Option<Box<u8>>, ()>::with_zeros_left(3) };
+        assert_eq!(c.left_slice(), Some(&[None, None, None][..]));
     }
 
     #[test]
-    fn replace() {
+    #[should_panic(expected = "over the limit")]
+    fn with_default_left_too_long() {
+        CW::with_default_left(4096);
+    }
+
+    #[test]
+    fn from_vec() {
+        let v = vec!["a".to_owned(), "b".to_owned(), "c".to_owned()];
+        let c = CW::from(v);
+        assert_left(&c, &["a", "b", "c"]);
         unsafe {
-            let me = B::create(4);
-            B::insert(me, 0, "Hello".to_owned());
-            *B::get_mut(me, 0) = "World".to_owned();
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 1);
-            assert_eq!(me_ref.capacity(), 4);
-            assert_eq!(B::get(me, 0), "World");
-            B::dispose(me);
+            assert_eq!(CoWecBlock::capacity(c.left_block().unwrap()), 4);
         }
+        let c = CW::from(Vec::new());
+        assert!(c.is_left());
+        assert_left(&c, &[]);
     }
 
     #[test]
-    fn remove() {
+    fn from_slice() {
+        let v = ["a".to_owned(), "b".to_owned()];
+        let c = CW::from(&v[..]);
+        assert_left(&c, &["a", "b"]);
+        assert_eq!(v.len(), 2);
+        assert!(CW::from(&[][..]).is_left());
+    }
+
+    #[test]
+    fn into_vec() {
+        let c = left_from(&["a", "b"]);
+        let d = c.clone();
+        // Shared, gets cloned
+        let v: Vec<String> = c.into();
+        assert_eq!(v, vec!["a", "b"]);
+        assert_left(&d, &["a", "b"]);
+        // Unique, gets moved
+        let v: Vec<String> = d.into();
+        assert_eq!(v, vec!["a", "b"]);
+        assert!(Vec::from(CW::new_right()).is_empty());
+        assert!(Vec::from(CW::new_stub()).is_empty());
+    }
+
+    #[test]
+    fn uninit_left() {
+        let mut c = unsafe { CW::uninit_left(3) };
+        let data = c.as_left_mut_ptr().unwrap();
+        for (i, s) in ["a", "b", "c"].iter().enumerate() {
+            unsafe { ptr::write(data.add(i), (*s).to_owned()) };
+        }
+        let c = unsafe { c.assume_init_left() };
+        assert_left(&c, &["a", "b", "c"]);
+    }
+
+    #[test]
+    fn as_left_mut_ptr() {
+        let mut c = left_from(&["a", "b"]);
+        let d = c.clone();
+        let data = c.as_left_mut_ptr().unwrap();
+        unsafe { (*data.add(1)).push('!') };
+        assert_left(&c, &["a", "b!"]);
+        assert_left(&d, &["a", "b"]);
+        assert!(CW::new_right().as_left_mut_ptr().is_none());
+    }
+
+    #[test]
+    fn boxed_slice() {
+        let b: Box<[String]> = vec!["a".to_owned(), "b".to_owned()].into_boxed_slice();
+        let c = CW::from(b);
+        assert_left(&c, &["a", "b"]);
+        let d = c.clone();
+        let b = c.into_boxed_slice_left().unwrap();
+        assert_eq!(&*b, &["a", "b"]);
+        let b = d.into_boxed_slice_left().unwrap();
+        assert_eq!(&*b, &["a", "b"]);
+        let empty = CW::from(Vec::new().into_boxed_slice());
+        assert!(empty.into_boxed_slice_left().unwrap().is_empty());
+        assert!(CW::new_right().into_boxed_slice_left().is_none());
+        assert!(CW::new_stub().into_boxed_slice_left().is_none());
+    }
+
+    #[test]
+    fn try_from_exact_left() {
+        type C = CoWec<RCell, u8, ()>;
+        let v = vec![0u8; 4096];
+        let c = C::try_from_exact_left(&v[..4095]).unwrap();
+        assert_eq!(c.len_left(), Some(4095));
+        let err = C::try_from_exact_left(&v).err().unwrap();
+        assert_eq!(
+            err,
+            CapacityError {
+                requested: 4096,
+                limit: 4095
+            }
+        );
+        assert!(C::try_from_exact_left(&[]).is_ok());
+    }
+
+    #[test]
+    fn large() {
+        type L = LargeCoWec<RCell, u32, ()>;
+        assert_eq!(CoWecBlock::<RCell, u32, u32>::DATA_OFFSET, 8);
+        assert_eq!(CoWecBlock::<RCell, u32>::DATA_OFFSET, 4);
+        let mut c = L::new_left();
+        for i in 0..10_000 {
+            c.push_left(i).unwrap();
+        }
+        assert_eq!(c.len_left(), Some(10_000));
         unsafe {
-            let me = B::create(4);
-            B::insert(me, 0, "Hello".to_owned());
-            B::insert(me, 1, "World".to_owned());
-            assert_eq!(B::remove(me, 0), "Hello");
-            assert_eq!(B::remove(me, 0), "World");
-            let me_ref = &mut *me;
-            assert_eq!(me_ref.len(), 0);
-            assert_eq!(me_ref.capacity(), 4);
-            B::dispose(me);
+            assert_eq!(CoWecBlock::capacity(c.left_block().unwrap()), 16_384);
         }
+        let d = c.clone();
+        assert_eq!(c.remove_left(5000), 5000);
+        assert_eq!(c.get_left(5000), Some(&5001));
+        assert_eq!(d.get_left(5000), Some(&5000));
+        let v = Vec::from(d);
+        assert_eq!(v, (0..10_000).collect::<Vec<_>>());
+        assert_eq!(L::from(v).len_left(), Some(10_000));
     }
 
-    type CW = CoWec::<RCell, String, usize>;
+    #[test]
+    fn large_limit() {
+        type LB = CoWecBlock<RCell, u8, u32>;
+        assert_eq!(LB::LEN_MASK, (1 << 27) - 1);
+        let c = LargeCoWec::<RCell, u8, ()>::try_from_exact_left(&[0; 5000]);
+        assert_eq!(c.unwrap().len_left(), Some(5000));
+        unsafe {
+            // A capacity that doesn't fit into the 4 bits of the small encoding.
+            let block = LB::create(1 << 20);
+            assert_eq!(LB::capacity(block), 1 << 20);
+            assert_eq!((*block).len(), 0);
+            LB::dispose(block);
+        }
+    }
 
-    /// Check construction & destruction of the empty thing
     #[test]
-    fn create_empty() {
-        let c = CW::new_stub();
+    fn structural_eq_left() {
+        type C = CoWec<RCell, u32, ()>;
+        let c = C::from(vec![1, 2, 3]);
+        unsafe {
+            assert!(c.structural_eq_left(&c.clone()));
+            assert!(c.structural_eq_left(&C::from(vec![1, 2, 3])));
+            assert!(!c.structural_eq_left(&C::from(vec![1, 2, 4])));
+            assert!(!c.structural_eq_left(&C::from(vec![1, 2])));
+            assert!(C::new_left().structural_eq_left(&C::new_left()));
+            assert!(!c.structural_eq_left(&C::new_right()));
+            assert!(!C::new_stub().structural_eq_left(&C::new_stub()));
+        }
+    }
+
+    #[test]
+    fn transform() {
+        let mut c = CoWec::<RCell, String, usize>::from_right_block(unsafe {
+            let block = CoWecBlock::create(4);
+            for i in 0..3 {
+                CoWecBlock::insert(block, i, i * 10);
+            }
+            block
+        });
+        let shared = c.clone();
+        c.transform_to_left(|n| n.to_string());
+        assert_left(&c, &["0", "10", "20"]);
+        assert!(shared.is_right());
+        // No-op on the already left variant
+        c.transform_to_left(|_| unreachable!());
+        assert_left(&c, &["0", "10", "20"]);
+        c.transform_to_right(|s| s.parse().unwrap());
+        assert!(c == shared);
+
+        let mut stub = CW::new_stub();
+        stub.transform_to_left(|_| unreachable!());
+        assert_left(&stub, &[]);
+    }
+
+    #[test]
+    fn transform_panic() {
+        let live = Rc::new(Cell::new(0));
+        let mut c = CoWec::<RCell, Bomb, usize>::from_right_block(unsafe {
+            let block = CoWecBlock::create(8);
+            for i in 0..5 {
+                CoWecBlock::insert(block, i, i);
+            }
+            block
+        });
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            c.transform_to_left(|_| {
+                calls += 1;
+                if calls == 3 {
+                    std::panic!("Boom");
+                }
+                live.set(live.get() + 1);
+                Bomb {
+                    live: Rc::clone(&live),
+                    clones_left: Rc::new(Cell::new(0)),
+                }
+            })
+        }));
+        assert!(result.is_err());
         assert!(c.is_stub());
-        assert!(!c.is_left());
-        assert!(!c.is_right());
+        assert_eq!(live.get(), 0);
     }
 
     #[test]
-    fn create_left() {
-        let c = CW::new_left();
-        assert!(!c.is_stub());
-        assert!(c.is_left());
-        assert!(!c.is_right());
+    fn either() {
+        let mut c = left_hello_world();
+        let shared = c.clone();
+        let len = |l: &[String]| l.len();
+        assert_eq!(c.either(len, |r| r.len() + 10), Some(2));
+        let upper = |l: &mut [String]| l[0].make_ascii_uppercase();
+        assert_eq!(c.either_mut(upper, |_| unreachable!()), Some(()));
+        assert_left(&c, &["HELLO", "World"]);
+        assert_left(&shared, &["Hello", "World"]);
+
+        let mut r = CW::new_right();
+        assert_eq!(r.either(len, |r| r.len() + 10), Some(10));
+        assert_eq!(r.either_mut(|_| 1, |_| 2), Some(2));
+
+        let mut stub = CW::new_stub();
+        assert_eq!(stub.either(len, |r| r.len()), None);
+        assert_eq!(stub.either_or_default(len, |_| 42), 0);
+        assert_eq!(stub.either_mut(|_| 1, |_| 2), None);
     }
 
     #[test]
-    fn create_right() {
-        let c = CW::new_right();
-        assert!(!c.is_stub());
-        assert!(!c.is_left());
-        assert!(c.is_right());
+    fn lengths() {
+        let c = left_from(&["a", "b", "c"]);
+        assert_eq!(c.len_left(), Some(3));
+        assert_eq!(c.len_right(), None);
+        assert_eq!(c.capacity_left(), Some(4));
+        assert_eq!(c.capacity_right(), None);
+        assert_eq!(c.len(), 3);
+        assert!(!c.is_empty());
+
+        let r = CW::new_right();
+        assert_eq!(r.len_left(), None);
+        assert_eq!(r.len_right(), Some(0));
+        assert_eq!(r.capacity_right(), Some(2));
+        assert!(r.is_empty());
+
+        let stub = CW::new_stub();
+        assert_eq!(stub.len_left(), None);
+        assert_eq!(stub.len_right(), None);
+        assert_eq!(stub.capacity_left(), None);
+        assert_eq!(stub.len(), 0);
+        assert!(stub.is_empty());
     }
 
     #[test]
-    #[allow(clippy::redundant_clone)]
-    fn clone_stub() {
-        let c = CW::new_stub();
-        let _d = c.clone();
+    fn reserve_left() {
+        let mut c = left_hello_world();
+        let shared = c.clone();
+        c.reserve_left(3);
+        assert_eq!(c.capacity_left(), Some(8));
+        assert_left(&c, &["Hello", "World"]);
+        assert_eq!(shared.capacity_left(), Some(2));
+        // Already fits
+        c.reserve_left_exact(6);
+        assert_eq!(c.capacity_left(), Some(8));
+        c.reserve_left_exact(7);
+        assert_eq!(c.capacity_left(), Some(9));
+        assert_left(&c, &["Hello", "World"]);
+        c.push_left("!".to_owned()).unwrap();
+        c.reserve_left_exact(14);
+        assert_eq!(c.capacity_left(), Some(17));
+        c.reserve_left_exact(16);
+        assert_eq!(c.capacity_left(), Some(19));
+        // A power of two gets the usual encoding
+        c.reserve_left_exact(29);
+        assert_eq!(c.capacity_left(), Some(32));
+        c.reserve_left(30);
+        assert_eq!(c.capacity_left(), Some(64));
+        c.shrink_to_fit_left();
+        assert_eq!(c.pop_left().as_deref(), Some("!"));
+
+        let err = c.try_reserve_left(4094).unwrap_err();
+        assert_eq!(
+            err,
+            TryReserveError::CapacityOverflow(CapacityError {
+                requested: 4096,
+                limit: 4095,
+            })
+        );
+        assert_eq!(c.try_reserve_left(usize::MAX).map_err(|_| ()), Err(()));
+        assert_left(&c, &["Hello", "World"]);
+        assert_eq!(c.try_reserve_left(4093), Ok(()));
+        assert_eq!(c.capacity_left(), Some(4096));
+
+        let mut r = CW::new_right();
+        assert_eq!(r.try_reserve_left(1), Err(TryReserveError::WrongVariant));
     }
 
     #[test]
-    #[allow(clippy::redundant_clone)]
-    fn clone_left_empty() {
-        let c = CW::new_left();
-        let _d = c.clone();
+    fn shrink() {
+        let mut c = left_from(&["a", "b", "c"]);
+        c.reserve_left(10);
+        assert_eq!(c.capacity_left(), Some(16));
+        c.shrink_to_left(5);
+        assert_eq!(c.capacity_left(), Some(8));
+        // Never grows
+        c.shrink_to_left(100);
+        assert_eq!(c.capacity_left(), Some(8));
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(3));
+        assert_left(&c, &["a", "b", "c"]);
+        // The tight block can still be modified, keeping its capacity
+        assert_eq!(c.pop_left().as_deref(), Some("c"));
+        assert_eq!(c.capacity_left(), Some(3));
+        c.push_left("d".to_owned()).unwrap();
+        c.push_left("e".to_owned()).unwrap();
+        assert_left(&c, &["a", "b", "d", "e"]);
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(4));
+
+        // Shared blocks are left alone
+        let mut c = left_from(&["a", "b", "c"]);
+        let shared = c.clone();
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(4));
+        drop(shared);
+
+        let mut e = CW::new_left();
+        e.shrink_to_fit_left();
+        assert_eq!(e.capacity_left(), Some(0));
+        e.push_left("a".to_owned()).unwrap();
+        assert_left(&e, &["a"]);
+    }
+
+    #[test]
+    fn shrink_right() {
+        let mut r = CW::new_right();
+        r.shrink_to_fit_right();
+        assert_eq!(r.capacity_right(), Some(0));
+        assert_eq!(r.len_right(), Some(0));
+        let mut l = CW::new_left();
+        l.shrink_to_right(0);
+        assert_eq!(l.capacity_left(), Some(2));
+    }
+
+    #[test]
+    fn extend_from_cowec_left() {
+        let mut c = left_from(&["a", "b"]);
+        let other = c.clone();
+        c.extend_from_cowec_left(&other);
+        assert_eq!(Vec::from(c.clone()), ["a", "b", "a", "b"]);
+        assert_eq!(Vec::from(other.clone()), ["a", "b"]);
+        let mut c = other.clone();
+        c.extend_from_cowec_left(&CW::new_left());
+        c.extend_from_cowec_left(&CW::new_right());
+        assert!(CW::ptr_eq(&c, &other));
+        let mut r = CW::new_right();
+        r.extend_from_cowec_left(&other);
+        assert_eq!(r.len_right(), Some(0));
+
+        type C = CoWec<RCell, u32, ()>;
+        let mut c = C::from(&[1, 2, 3][..]);
+        let other = C::from((4..100).collect::<Vec<_>>());
+        c.extend_from_cowec_left_copy(&other);
+        assert_eq!(Vec::from(c.clone()), (1..100).collect::<Vec<_>>());
+        let shared = c.clone();
+        c.extend_from_cowec_left_copy(&shared);
+        assert_eq!(c.len_left(), Some(198));
+        assert_eq!(shared.len_left(), Some(99));
+        assert_eq!(c.get_left(197), Some(&99));
+    }
+
+    #[test]
+    fn extend_from_cowec_left_panic() {
+        let live = Rc::new(Cell::new(0));
+        let clones_left = Rc::new(Cell::new(1));
+        let mut c = bombs(&live, &clones_left, 1);
+        let other = bombs(&live, &clones_left, 3);
+        let result = catch_unwind(AssertUnwindSafe(|| c.extend_from_cowec_left(&other)));
+        assert!(result.is_err());
+        assert_eq!(c.len_left(), Some(2));
+        assert_eq!(live.get(), 5);
+        drop(c);
+        drop(other);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn replace_left() {
+        let mut c = left_from(&["a", "b"]);
+        let shared = c.clone();
+        assert_eq!(c.replace_left(1, "c".to_owned()), Some("b".to_owned()));
+        assert_eq!(Vec::from(c.clone()), ["a", "c"]);
+        assert_eq!(Vec::from(shared.clone()), ["a", "b"]);
+        let mut d = shared.clone();
+        assert_eq!(d.replace_left(2, "x".to_owned()), None);
+        assert!(CW::ptr_eq(&d, &shared));
+        assert_eq!(CW::new_right().replace_left(0, "x".to_owned()), None);
+
+        // The old value is moved out, not dropped
+        let live = Rc::new(Cell::new(0));
+        let clones_left = Rc::new(Cell::new(0));
+        let mut c = bombs(&live, &clones_left, 2);
+        let new = bombs(&live, &clones_left, 1).pop_left().unwrap();
+        let old = c.replace_left(0, new).unwrap();
+        assert_eq!(live.get(), 3);
+        drop(old);
+        assert_eq!(live.get(), 2);
+        drop(c);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn ptr_eq() {
+        let c = left_from(&["a"]);
+        let mut d = c.clone();
+        assert!(CW::ptr_eq(&c, &d));
+        assert!(!CW::ptr_eq(&c, &left_from(&["a"])));
+        d.push_left("b".to_owned()).unwrap();
+        assert!(!CW::ptr_eq(&c, &d));
+        assert!(CW::ptr_eq(&CW::new_stub(), &CW::new_stub()));
+        assert!(!CW::ptr_eq(&CW::new_left(), &CW::new_left()));
+    }
+
+    #[test]
+    fn default() {
+        let c = CW::default();
+        assert!(c.is_stub());
+        assert_eq!(c.ptr, 0);
+
+        #[derive(Default)]
+        struct Wrapper {
+            inner: CoWec<AtomicRCell, u8, u16>,
+        }
+        assert!(Wrapper::default().inner.is_stub());
+    }
+
+    #[test]
+    fn display() {
+        type C = CoWec<RCell, i32, f64>;
+        let c = C::from(vec![1, -2, 3]);
+        assert_eq!(c.to_string(), format!("{:?}", vec![1, -2, 3]));
+        assert_eq!(C::new_left().to_string(), "[]");
+        let r = C::from_right_block(unsafe {
+            let block = CoWecBlock::create(2);
+            CoWecBlock::insert(block, 0, 1.5);
+            CoWecBlock::insert(block, 1, 2.0);
+            block
+        });
+        assert_eq!(r.to_string(), "[1.5, 2]R");
+        assert_eq!(C::new_stub().to_string(), "()");
+    }
+
+    /// Counts how many times it got dropped.
+    #[derive(Clone)]
+    struct ZstDrop;
+
+    std::thread_local! {
+        static ZST_DROPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    impl Drop for ZstDrop {
+        fn drop(&mut self) {
+            ZST_DROPS.with(|d| d.set(d.get() + 1));
+        }
+    }
+
+    #[test]
+    fn zst() {
+        type C = CoWec<RCell, (), usize>;
+        type ZB = CoWecBlock<RCell, ()>;
+        assert_eq!(ZB::layout(1024).size(), ZB::DATA_OFFSET);
+        let mut c = C::new_left();
+        for _ in 0..100 {
+            c.push_left(()).unwrap();
+        }
+        assert_eq!(c.len_left(), Some(100));
+        assert_eq!(c.capacity_left(), Some(128));
+        c.insert_left(50, ()).unwrap();
+        c.remove_left(0);
+        assert_eq!(c.pop_left(), Some(()));
+        assert_eq!(c.len_left(), Some(99));
+        assert_eq!(c.left_slice().unwrap().iter().count(), 99);
+        assert_eq!(c.chunks_left(10).count(), 10);
+        c.shrink_to_fit_left();
+        assert_eq!(c.capacity_left(), Some(99));
+        let d = c.clone();
+        c.push_left(()).unwrap();
+        assert_eq!(d.len_left(), Some(99));
+        assert_eq!(c.len_left(), Some(100));
+        // Up to the limit
+        let mut full = C::new_left();
+        full.reserve_left(4095);
+        for _ in 0..4095 {
+            full.push_left(()).unwrap();
+        }
+        assert_eq!(full.push_left(()), Err(InsertError::Full));
+    }
+
+    #[test]
+    fn zst_drops() {
+        ZST_DROPS.with(|d| d.set(0));
+        let mut c = CoWec::<RCell, ZstDrop, usize>::new_left();
+        for _ in 0..10 {
+            c.push_left(ZstDrop).unwrap();
+        }
+        drop(c.pop_left());
+        assert_eq!(ZST_DROPS.with(Cell::get), 1);
+        let d = c.clone();
+        // Copies the block, leaving the original with d
+        c.remove_left(0);
+        assert_eq!(ZST_DROPS.with(Cell::get), 2);
+        drop(c);
+        drop(d);
+        assert_eq!(ZST_DROPS.with(Cell::get), 2 + 8 + 9);
+    }
+
+    /// Over-aligned element types keep working (and keep the tag bits free).
+    #[test]
+    fn alignments() {
+        macro_rules! check_align {
+            ($($name:ident: $align:literal),*) => {$(
+                #[derive(Clone, Copy, Debug, PartialEq)]
+                #[repr(align($align))]
+                struct $name(u8);
+
+                let mut l = CoWec::<RCell, $name, $name>::new_left();
+                for i in 0..5 {
+                    l.push_left($name(i)).unwrap();
+                }
+                let align = mem::align_of::<$name>();
+                assert_eq!(align, $align);
+                let block = l.left_block().unwrap();
+                assert_eq!(block as usize % align.max(4), 0);
+                assert_eq!(unsafe { CoWecBlock::get_data(block) } as usize % align, 0);
+                assert_eq!(l.left_slice().unwrap()[4], $name(4));
+                let mut r = l.clone();
+                r.transform_to_right(|x| x);
+                assert!(r.is_right());
+                assert_eq!(r.right_slice().unwrap()[3], $name(3));
+                assert!(l.is_left());
+            )*};
+        }
+        check_align!(A1: 1, A2: 2, A4: 4, A8: 8, A16: 16, A32: 32, A64: 64);
+    }
+
+    #[test]
+    fn search() {
+        let c = left_from(&["a", "b", "a"]);
+        assert!(c.contains_left(&"b".to_owned()));
+        assert!(!c.contains_left(&"c".to_owned()));
+        assert_eq!(c.position_left(|s| s == "a"), Some(0));
+        assert_eq!(c.rposition_left(|s| s == "a"), Some(2));
+        assert_eq!(c.position_left(|s| s == "c"), None);
+        for other in [CW::new_stub(), CW::new_right(), CW::new_left()] {
+            assert!(!other.contains_left(&"a".to_owned()));
+            assert_eq!(other.position_left(|_| true), None);
+            assert_eq!(other.rposition_left(|_| true), None);
+        }
+    }
+
+    #[test]
+    fn fill() {
+        let mut c = left_hello_world();
+        let shared = c.clone();
+        c.fill_left("x".to_owned());
+        assert_left(&c, &["x", "x"]);
+        assert_left(&shared, &["Hello", "World"]);
+        let mut n = 0;
+        c.fill_left_with(|| {
+            n += 1;
+            n.to_string()
+        });
+        assert_left(&c, &["1", "2"]);
+        // The wrong variant is left alone
+        c.fill_right(42);
+        assert_left(&c, &["1", "2"]);
+
+        let mut r = CoWec::<RCell, String, usize>::from_right_block(unsafe {
+            let block = CoWecBlock::create(4);
+            for i in 0..3 {
+                CoWecBlock::insert(block, i, i);
+            }
+            block
+        });
+        r.fill_right(7);
+        assert_eq!(r.right_slice(), Some(&[7, 7, 7][..]));
+        r.fill_right_with(|| 1);
+        assert_eq!(r.right_slice(), Some(&[1, 1, 1][..]));
+    }
+
+    #[test]
+    fn fill_panic() {
+        let mut c = left_from(&["a", "b", "c"]);
+        let mut n = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            c.fill_left_with(|| {
+                n += 1;
+                if n == 2 {
+                    std::panic!("Boom");
+                }
+                "x".to_owned()
+            })
+        }));
+        assert!(result.is_err());
+        assert_left(&c, &["x", "b", "c"]);
+    }
+
+    #[test]
+    fn splice_left() {
+        let mut c = left_from(&["a", "b", "c", "d"]);
+        let shared = c.clone();
+        let removed: Vec<_> = c.splice_left(1..3, vec!["x".to_owned()]).collect();
+        assert_eq!(removed, ["b", "c"]);
+        assert_left(&c, &["a", "x", "d"]);
+        assert_left(&shared, &["a", "b", "c", "d"]);
+        // Growing over the capacity
+        let new = (0..6).map(|i| i.to_string());
+        assert_eq!(c.splice_left(1..=1, new).len(), 1);
+        assert_left(&c, &["a", "0", "1", "2", "3", "4", "5", "d"]);
+        // Just removing and just inserting
+        c.splice_left(..7, None).for_each(drop);
+        assert_left(&c, &["d"]);
+        assert_eq!(c.splice_left(1.., vec!["e".to_owned()]).count(), 0);
+        assert_left(&c, &["d", "e"]);
+        c.splice_left(.., vec![]);
+        assert_left(&c, &[]);
+    }
+
+    #[test]
+    fn splice_left_panic() {
+        let mut c = left_from(&["a", "b", "c"]);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let replacement = (0..3).map(|i| {
+                if i == 2 {
+                    std::panic!("Boom");
+                }
+                i.to_string()
+            });
+            c.splice_left(0..1, replacement);
+        }));
+        assert!(result.is_err());
+        assert_left(&c, &["a", "b", "c"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "range end index 4 out of range for slice of length 3")]
+    fn splice_left_out_of_range() {
+        let mut c = left_from(&["a", "b", "c"]);
+        c.splice_left(1..4, None);
+    }
+
+    #[test]
+    fn copy_within_left() {
+        type C = CoWec<RCell, u8, ()>;
+        let check = |src: std::ops::Range<usize>, dst, expected: &[u8]| {
+            let mut c = C::from(&[0, 1, 2, 3, 4, 5][..]);
+            let shared = c.clone();
+            c.copy_within_left(src, dst);
+            assert_eq!(c.left_slice(), Some(expected));
+            assert_eq!(shared.left_slice(), Some(&[0, 1, 2, 3, 4, 5][..]));
+        };
+        // Overlapping forward and backward
+        check(0..4, 2, &[0, 1, 0, 1, 2, 3]);
+        check(2..6, 0, &[2, 3, 4, 5, 4, 5]);
+        // Not overlapping
+        check(0..2, 4, &[0, 1, 2, 3, 0, 1]);
+        // Empty
+        check(3..3, 6, &[0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_within_left_out_of_bounds() {
+        let mut c = CoWec::<RCell, u8, ()>::from(&[0, 1, 2][..]);
+        c.copy_within_left(0..2, 2);
+    }
+
+    #[test]
+    fn bytes_left() {
+        type C = CoWec<RCell, u32, ()>;
+        let c = C::from(vec![1, 0x0102_0304]);
+        let bytes = unsafe { c.clone().into_bytes_left() };
+        let mut expected = 1u32.to_ne_bytes().to_vec();
+        expected.extend_from_slice(&0x0102_0304u32.to_ne_bytes());
+        assert_eq!(bytes, expected);
+        // Unaligned on purpose
+        let mut unaligned = vec![0u8];
+        unaligned.extend_from_slice(&bytes);
+        let back = unsafe { C::from_bytes_left(&unaligned[1..]) }.unwrap();
+        assert!(back == c);
+
+        assert!(unsafe { C::from_bytes_left(&bytes[..7]) }.is_none());
+        assert!(unsafe { C::from_bytes_left(&[]) }.unwrap() == C::new_left());
+        assert!(unsafe { CoWec::<RCell, (), ()>::from_bytes_left(&[]) }.is_none());
+        assert!(unsafe { C::new_right().into_bytes_left() }.is_empty());
+    }
+
+    #[test]
+    fn adler32_left() {
+        let c = CoWec::<RCell, u8, ()>::from(&b"Wikipedia"[..]);
+        unsafe {
+            assert_eq!(c.adler32_left(), 0x11e6_0398);
+            assert_eq!(CoWec::<RCell, u8, ()>::new_right().adler32_left(), 1);
+        }
+    }
+
+    #[test]
+    fn fingerprint_left() {
+        type C = CoWec<RCell, u32, ()>;
+        let c = C::from(vec![1, 2, 3]);
+        unsafe {
+            let fp = c.fingerprint_left();
+            assert_eq!(fp, c.clone().fingerprint_left());
+            assert_eq!(fp, C::from(vec![1, 2, 3]).fingerprint_left());
+            assert_ne!(fp, C::from(vec![1, 2, 4]).fingerprint_left());
+            assert_ne!(fp, C::from(vec![1, 2, 3, 0]).fingerprint_left());
+            let empty = C::new_left().fingerprint_left();
+            assert_eq!(empty, C::new_right().fingerprint_left());
+        }
     }
 }
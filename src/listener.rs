@@ -0,0 +1,180 @@
+//! Notifications about modifications.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+use super::{CoWec, DoublingGrowth, GrowthPolicy, InsertError, LenCap, RefCnt};
+
+/// A wrapper around a [`CoWec`] that calls registered callbacks whenever it is modified.
+///
+/// Read-only access goes through [`Deref`]. The modifications are available only through the
+/// methods of the wrapper, which notify the callbacks after a successful change (a failed
+/// insertion or popping from an empty `CoWec` don't count).
+pub struct ChangeListener<R: RefCnt, T, U, L: LenCap = u16, G: GrowthPolicy = DoublingGrowth> {
+    cowec: CoWec<R, T, U, L, G>,
+    callbacks: Vec<Box<dyn FnMut()>>,
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> ChangeListener<R, T, U, L, G> {
+    /// Wraps the `CoWec`, with no callbacks registered yet.
+    pub fn new(cowec: CoWec<R, T, U, L, G>) -> Self {
+        Self {
+            cowec,
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Registers another callback to be called on each modification.
+    pub fn on_change<F: FnMut() + 'static>(&mut self, callback: F) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Unwraps the `CoWec`, dropping the callbacks.
+    pub fn into_inner(self) -> CoWec<R, T, U, L, G> {
+        self.cowec
+    }
+
+    fn notify(&mut self) {
+        for callback in &mut self.callbacks {
+            callback();
+        }
+    }
+
+    /// [`CoWec::push_left`], with a notification.
+    pub fn push_left(&mut self, val: T) -> Result<(), InsertError>
+    where
+        T: Clone,
+    {
+        self.cowec.push_left(val)?;
+        self.notify();
+        Ok(())
+    }
+
+    /// [`CoWec::pop_left`], with a notification.
+    pub fn pop_left(&mut self) -> Option<T>
+    where
+        T: Clone,
+    {
+        let val = self.cowec.pop_left()?;
+        self.notify();
+        Some(val)
+    }
+
+    /// [`CoWec::insert_left`], with a notification.
+    pub fn insert_left(&mut self, pos: usize, val: T) -> Result<(), InsertError>
+    where
+        T: Clone,
+    {
+        self.cowec.insert_left(pos, val)?;
+        self.notify();
+        Ok(())
+    }
+
+    /// [`CoWec::remove_left`], with a notification.
+    ///
+    /// # Panics
+    ///
+    /// If this is not the left variant or if `pos >= len`.
+    pub fn remove_left(&mut self, pos: usize) -> T
+    where
+        T: Clone,
+    {
+        let val = self.cowec.remove_left(pos);
+        self.notify();
+        val
+    }
+
+    /// [`CoWec::fill_left`], with a notification if there was anything to overwrite.
+    ///
+    /// That is, only if this is the left variant and not empty.
+    pub fn fill_left(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        if self.cowec.len_left().unwrap_or(0) > 0 {
+            self.cowec.fill_left(value);
+            self.notify();
+        }
+    }
+
+    /// [`CoWec::get_left_mut`], with a notification.
+    ///
+    /// The callbacks are called before the modification happens (as the element is being
+    /// borrowed), but only if the element exists.
+    pub fn get_left_mut(&mut self, index: usize) -> Option<&mut T>
+    where
+        T: Clone,
+    {
+        self.cowec.get_left(index)?;
+        self.notify();
+        self.cowec.get_left_mut(index)
+    }
+
+    /// Allows arbitrary modification of the wrapped `CoWec`, with a notification afterwards.
+    pub fn modify<O, F: FnOnce(&mut CoWec<R, T, U, L, G>) -> O>(&mut self, f: F) -> O {
+        let result = f(&mut self.cowec);
+        self.notify();
+        result
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Deref for ChangeListener<R, T, U, L, G> {
+    type Target = CoWec<R, T, U, L, G>;
+
+    fn deref(&self) -> &CoWec<R, T, U, L, G> {
+        &self.cowec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::RCell;
+
+    #[test]
+    fn notifies() {
+        let changes = Rc::new(Cell::new(0));
+        let mut l = ChangeListener::new(CoWec::<RCell, u32, ()>::new_left());
+        for _ in 0..2 {
+            let changes = Rc::clone(&changes);
+            l.on_change(move || changes.set(changes.get() + 1));
+        }
+        l.push_left(1).unwrap();
+        assert_eq!(changes.get(), 2);
+        l.insert_left(0, 0).unwrap();
+        *l.get_left_mut(1).unwrap() = 2;
+        assert_eq!(l.remove_left(0), 0);
+        assert_eq!(l.pop_left(), Some(2));
+        assert_eq!(changes.get(), 10);
+        // Nothing to fill
+        l.fill_left(4);
+        // No change, no notification
+        assert_eq!(l.pop_left(), None);
+        assert!(l.get_left_mut(0).is_none());
+        assert_eq!(changes.get(), 10);
+        l.modify(|c| c.push_left(3)).unwrap();
+        assert_eq!(changes.get(), 12);
+        assert_eq!(l.get_left(0), Some(&3));
+        l.fill_left(4);
+        assert_eq!(changes.get(), 14);
+        assert_eq!(l.get_left(0), Some(&4));
+        assert_eq!(l.into_inner().len_left(), Some(1));
+    }
+
+    #[test]
+    fn failed_no_notify() {
+        let changes = Rc::new(Cell::new(0));
+        let mut l = ChangeListener::new(CoWec::<RCell, u32, ()>::new_right());
+        let c = Rc::clone(&changes);
+        l.on_change(move || c.set(c.get() + 1));
+        assert_eq!(l.push_left(1), Err(InsertError::WrongVariant));
+        assert_eq!(l.insert_left(0, 1), Err(InsertError::WrongVariant));
+        assert_eq!(l.pop_left(), None);
+        l.fill_left(1);
+        assert_eq!(changes.get(), 0);
+    }
+}
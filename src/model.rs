@@ -0,0 +1,107 @@
+//! Checking of [`CoWec`]s against plain vectors.
+//!
+//! Shared by the randomized tests and the fuzz target (through the `fuzzing` feature), so both
+//! exercise the same operations. Not part of the public API.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::{CoWec, RCell};
+
+type C = CoWec<RCell, u32, ()>;
+
+/// Upper limit on the number of handles alive at once.
+const MAX_HANDLES: usize = 8;
+
+/// The most a default (`u16`) block can hold.
+const MAX_LEN: usize = 4095;
+
+/// An operation on one of the handles.
+///
+/// The positions are taken modulo the current length (plus one where the position right at the
+/// end makes sense), so any value can be used.
+#[derive(Copy, Clone, Debug)]
+pub enum Op {
+    /// Insert the value at the position.
+    InsertAt(usize, u32),
+    /// Remove from the position, or pop (and get nothing) if empty.
+    RemoveAt(usize),
+    /// Look up an element, sometimes one past the end.
+    GetAt(usize),
+    /// Add a clone of the handle, sharing its block.
+    Clone,
+    /// Drop the handle (unless it's the last one).
+    DropClone,
+}
+
+/// A few `CoWec`s (possibly sharing blocks), each with a vector holding what it should contain.
+pub struct Model {
+    handles: Vec<(C, Vec<u32>)>,
+}
+
+impl Model {
+    /// Starts with a single empty left `CoWec`.
+    pub fn new() -> Self {
+        Self {
+            handles: vec![(C::new_left(), Vec::new())],
+        }
+    }
+
+    /// The current number of handles.
+    pub fn handles(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// The length of the handle (modulo the number of handles).
+    pub fn len(&self, handle: usize) -> usize {
+        self.handles[handle % self.handles.len()].1.len()
+    }
+
+    /// Applies the operation to the handle (modulo the number of handles), then checks all the
+    /// handles against their vectors.
+    ///
+    /// # Panics
+    ///
+    /// If any of them doesn't match.
+    pub fn apply(&mut self, handle: usize, op: Op) {
+        let count = self.handles.len();
+        let h = handle % count;
+        let (c, model) = &mut self.handles[h];
+        match op {
+            Op::InsertAt(pos, val) => {
+                let pos = pos % (model.len() + 1);
+                match c.insert_left(pos, val) {
+                    Ok(()) => model.insert(pos, val),
+                    Err(_) => assert_eq!(model.len(), MAX_LEN),
+                }
+            }
+            Op::RemoveAt(pos) if !model.is_empty() => {
+                let pos = pos % model.len();
+                assert_eq!(c.remove_left(pos), model.remove(pos));
+            }
+            Op::RemoveAt(_) => assert_eq!(c.pop_left(), None),
+            // Deliberately out of bounds sometimes (pos == len)
+            Op::GetAt(pos) => {
+                let pos = pos % (model.len() + 1);
+                assert_eq!(c.get_left(pos), model.get(pos));
+            }
+            Op::Clone if count < MAX_HANDLES => {
+                let copy = (c.clone(), model.clone());
+                self.handles.push(copy);
+            }
+            Op::DropClone if count > 1 => {
+                self.handles.swap_remove(h);
+            }
+            Op::Clone | Op::DropClone => (),
+        }
+        for (c, model) in &self.handles {
+            assert_eq!(c.left_slice(), Some(&model[..]));
+        }
+    }
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::new()
+    }
+}
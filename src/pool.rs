@@ -0,0 +1,167 @@
+//! Recycling of blocks.
+
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{CoWec, CoWecBlock, LenCap, RefCnt};
+
+/// Number of capacity classes.
+///
+/// The largest capacity is `2^LEN_BITS`, a larger block could hold more elements than its length
+/// can encode.
+const CLASSES: usize = <u16 as LenCap>::LEN_BITS as usize + 1;
+
+/// A pool of spare blocks.
+///
+/// Creating and destroying many short-lived [`CoWec`]s puts pressure on the allocator. Instead of
+/// dropping a [`CoWec`], it can be handed to the pool with [`dealloc`][BlockPool::dealloc]. If it
+/// is the last handle to its (left) block, the elements are dropped but the block itself is kept
+/// around and reused by a later [`alloc`][BlockPool::alloc] of the same capacity.
+///
+/// The spare blocks are returned to the allocator once the pool is dropped.
+pub struct BlockPool<R: RefCnt, T> {
+    /// The spare blocks, by capacity. The `i`th list holds blocks with capacity `2^i`.
+    ///
+    /// All of them are empty and nobody else holds a reference to them.
+    free: [Vec<*mut CoWecBlock<R, T>>; CLASSES],
+}
+
+impl<R: RefCnt, T> BlockPool<R, T> {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self {
+            free: Default::default(),
+        }
+    }
+
+    /// Creates an empty left [`CoWec`] able to hold at least `capacity` elements.
+    ///
+    /// A spare block is used if one is available.
+    ///
+    /// # Panics
+    ///
+    /// If the capacity is over the maximum of a block (4096).
+    pub fn alloc<U>(&mut self, capacity: usize) -> CoWec<R, T, U> {
+        let capacity = capacity.max(2).next_power_of_two();
+        let class = capacity.trailing_zeros() as usize;
+        assert!(class < CLASSES, "Capacity {} is too large", capacity);
+        let block = match self.free[class].pop() {
+            Some(block) => block,
+            None => unsafe { CoWecBlock::create(capacity) },
+        };
+        CoWec::from_left_block(block)
+    }
+
+    /// Disposes of a [`CoWec`], keeping its block for later reuse.
+    ///
+    /// The block is kept only if this is the last handle to a left block. In all other cases this
+    /// is the same as simply dropping the [`CoWec`].
+    pub fn dealloc<U>(&mut self, cowec: CoWec<R, T, U>) {
+        let block = match cowec.left_block() {
+            Some(block) => block,
+            None => return,
+        };
+        unsafe {
+            // Tight blocks don't have a capacity class we could put them into.
+            if !(*block).rcell.is_unique() || CoWecBlock::is_tight(block) {
+                return;
+            }
+            let class = CoWecBlock::capacity(block).trailing_zeros() as usize;
+            // We've taken over the reference, the block in the pool owns it now.
+            mem::forget(cowec);
+            CoWecBlock::clear(block);
+            self.free[class].push(block);
+        }
+    }
+
+    /// Number of spare blocks currently kept in the pool.
+    pub fn spare(&self) -> usize {
+        self.free.iter().map(Vec::len).sum()
+    }
+}
+
+impl<R: RefCnt, T> Default for BlockPool<R, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: RefCnt, T> Drop for BlockPool<R, T> {
+    fn drop(&mut self) {
+        for block in self.free.iter_mut().flat_map(|class| class.drain(..)) {
+            unsafe { CoWecBlock::dispose(block) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::RCell;
+
+    type P = BlockPool<RCell, Rc<()>>;
+    type CW = CoWec<RCell, Rc<()>, ()>;
+
+    #[test]
+    fn reuse() {
+        let mut pool = P::new();
+        let c: CW = pool.alloc(4);
+        let ptr = c.ptr;
+        pool.dealloc(c);
+        assert_eq!(pool.spare(), 1);
+        // Different capacity class, doesn't get the spare one.
+        let d: CW = pool.alloc(16);
+        let _: CW = pool.alloc(4096);
+        assert_ne!(d.ptr, ptr);
+        let e: CW = pool.alloc(3);
+        assert_eq!(e.ptr, ptr);
+        assert_eq!(pool.spare(), 0);
+        unsafe {
+            assert_eq!(CoWecBlock::capacity(e.left_block().unwrap()), 4);
+        }
+    }
+
+    #[test]
+    fn drops_elements() {
+        let rc = Rc::new(());
+        let mut pool = P::new();
+        let c: CW = pool.alloc(2);
+        unsafe {
+            CoWecBlock::insert(c.left_block().unwrap(), 0, Rc::clone(&rc));
+        }
+        assert_eq!(Rc::strong_count(&rc), 2);
+        pool.dealloc(c);
+        assert_eq!(Rc::strong_count(&rc), 1);
+        let c: CW = pool.alloc(2);
+        assert!(c.get_left(0).is_none());
+    }
+
+    #[test]
+    fn shared_not_reused() {
+        let mut pool = P::new();
+        let c: CW = pool.alloc(2);
+        let d = c.clone();
+        pool.dealloc(c);
+        assert_eq!(pool.spare(), 0);
+        // But the last one goes in
+        pool.dealloc(d);
+        assert_eq!(pool.spare(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity 8192 is too large")]
+    fn too_large() {
+        let _: CW = P::new().alloc(8192);
+    }
+
+    #[test]
+    fn other_variants() {
+        let mut pool = P::new();
+        pool.dealloc(CW::new_stub());
+        pool.dealloc(CW::new_right());
+        assert_eq!(pool.spare(), 0);
+    }
+}
@@ -0,0 +1,148 @@
+//! Sub-ranges of shared blocks.
+
+use core::ops::Deref;
+use core::slice;
+
+use super::{AtomicRCell, CoWec, CoWecBlock, LenCap, RefCnt};
+
+/// A read-only view into a sub-range of a block.
+///
+/// It holds a reference to the whole block (keeping it alive), but gives access only to its
+/// range. Created by [`CoWec::split_borrowed_left`].
+///
+/// A [`CoWec`] is a single tagged pointer and has no room to remember a range, therefore this is
+/// a separate type. To modify the content, turn it into an independent [`CoWec`] with
+/// [`to_left`][SlicedBlock::to_left], which copies only the elements in the range.
+pub struct SlicedBlock<R: RefCnt, T, L: LenCap = u16> {
+    block: *mut CoWecBlock<R, T, L>,
+    start: usize,
+    end: usize,
+}
+
+impl<R: RefCnt, T, L: LenCap> SlicedBlock<R, T, L> {
+    /// Creates a new view, taking over a reference to the block.
+    pub(crate) fn new(block: *mut CoWecBlock<R, T, L>, start: usize, end: usize) -> Self {
+        debug_assert!(start <= end);
+        debug_assert!(end <= unsafe { (*block).len() });
+        Self { block, start, end }
+    }
+
+    /// Creates a new left [`CoWec`] holding copies of the elements in the range.
+    pub fn to_left<U>(&self) -> CoWec<R, T, U, L>
+    where
+        T: Clone,
+    {
+        let mut cowec = CoWec::new_left();
+        for elem in self.iter() {
+            cowec
+                .push_left(elem.clone())
+                .expect("A sub-range doesn't fit into a block");
+        }
+        cowec
+    }
+}
+
+// The same reasoning as with CoWec itself.
+unsafe impl<T: Send + Sync, L: LenCap> Send for SlicedBlock<AtomicRCell, T, L> {}
+unsafe impl<T: Send + Sync, L: LenCap> Sync for SlicedBlock<AtomicRCell, T, L> {}
+
+impl<R: RefCnt, T, L: LenCap> Clone for SlicedBlock<R, T, L> {
+    fn clone(&self) -> Self {
+        let block = unsafe { CoWecBlock::inc_ref(self.block) };
+        Self::new(block as *mut _, self.start, self.end)
+    }
+}
+
+impl<R: RefCnt, T, L: LenCap> Deref for SlicedBlock<R, T, L> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        // The block is shared (at least by us), so nobody modifies it in place while we live.
+        unsafe {
+            let data = CoWecBlock::get_data(self.block).add(self.start);
+            slice::from_raw_parts(data.cast(), self.end - self.start)
+        }
+    }
+}
+
+impl<R: RefCnt, T, L: LenCap> Drop for SlicedBlock<R, T, L> {
+    fn drop(&mut self) {
+        unsafe { CoWecBlock::dec_ref(self.block) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::prelude::v1::*;
+
+    use crate::RCell;
+
+    type CW = crate::CoWec<RCell, String, ()>;
+
+    fn abc() -> CW {
+        let mut c = CW::new_left();
+        for s in &["a", "b", "c"] {
+            c.push_left((*s).to_owned()).unwrap();
+        }
+        c
+    }
+
+    #[test]
+    fn split() {
+        let c = abc();
+        let (l, r) = c.split_borrowed_left(1).unwrap();
+        assert_eq!(&*l, &["a"]);
+        assert_eq!(&*r, &["b", "c"]);
+        let (l, r) = c.split_borrowed_left(3).unwrap();
+        assert_eq!(l.len(), 3);
+        assert!(r.is_empty());
+        assert!(CW::new_right().split_borrowed_left(0).is_none());
+    }
+
+    #[test]
+    fn split_saturated() {
+        let c = abc();
+        let block = c.left_block().unwrap();
+        unsafe {
+            let rcell = &(*block).rcell;
+            let old = rcell.0.replace(u16::MAX - 1);
+            // Room for just one more, that one is given back
+            assert!(c.split_borrowed_left(1).is_none());
+            assert_eq!(rcell.0.get(), u16::MAX - 1);
+            rcell.0.set(u16::MAX);
+            assert!(c.split_borrowed_left(1).is_none());
+            rcell.0.set(old);
+        }
+        assert!(c.split_borrowed_left(1).is_some());
+    }
+
+    /// The halves keep the block alive and protect it from in-place modification.
+    #[test]
+    fn outlive_and_modify() {
+        let mut c = abc();
+        let (l, r) = c.split_borrowed_left(2).unwrap();
+        *c.get_left_mut(0).unwrap() = "x".to_owned();
+        assert_eq!(&*l, &["a", "b"]);
+        drop(c);
+        let r2 = r.clone();
+        drop(r);
+        assert_eq!(&*r2, &["c"]);
+    }
+
+    #[test]
+    fn to_left() {
+        let c = abc();
+        let (_, r) = c.split_borrowed_left(1).unwrap();
+        let mut d: CW = r.to_left();
+        d.push_left("d".to_owned()).unwrap();
+        assert_eq!(d.get_left(0).unwrap(), "b");
+        assert_eq!(d.get_left(2).unwrap(), "d");
+        assert!(d.get_left(3).is_none());
+        assert_eq!(&*r, &["b", "c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_out_of_bounds() {
+        let _ = abc().split_borrowed_left(4);
+    }
+}
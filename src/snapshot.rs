@@ -0,0 +1,109 @@
+//! Snapshots for change detection.
+
+use core::ops::Deref;
+
+use super::{CoWec, DoublingGrowth, GrowthPolicy, LenCap, RefCnt};
+
+/// A read-only snapshot of the left variant of a [`CoWec`].
+///
+/// It shares the block with the original (it is just another reference to it). As the block is
+/// shared, any modification of the original copies it first, which can be detected with
+/// [`has_diverged_from`][CoWec::has_diverged_from] without comparing the elements.
+///
+/// Created by [`CoWec::snapshot_left`].
+pub struct CoWecSnapshot<R: RefCnt, T, U, L: LenCap = u16, G: GrowthPolicy = DoublingGrowth> {
+    cowec: CoWec<R, T, U, L, G>,
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Clone for CoWecSnapshot<R, T, U, L, G> {
+    fn clone(&self) -> Self {
+        Self {
+            cowec: self.cowec.clone(),
+        }
+    }
+}
+
+impl<R: RefCnt, T, U, L: LenCap, G: GrowthPolicy> Deref for CoWecSnapshot<R, T, U, L, G> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.cowec
+            .left_slice()
+            .expect("Snapshot always holds the left variant")
+    }
+}
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Takes a snapshot of the current state of the left variant.
+    ///
+    /// Returns `None` if this is not the left variant.
+    pub fn snapshot_left(&self) -> Option<CoWecSnapshot<R, T, U, L, G>> {
+        if self.is_left() {
+            Some(CoWecSnapshot {
+                cowec: self.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Checks if this has been modified since the snapshot was taken.
+    ///
+    /// Compares just the identity of the blocks, not the elements. Therefore, replacing the
+    /// content with equal elements still counts as diverged, as does a snapshot of an unrelated
+    /// `CoWec` with the same content. On the other hand, a snapshot of any `CoWec` sharing the
+    /// same block (eg. a clone) does not count as diverged.
+    pub fn has_diverged_from(&self, snapshot: &CoWecSnapshot<R, T, U, L, G>) -> bool {
+        self.ptr != snapshot.cowec.ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, u32, ()>;
+
+    #[test]
+    fn diverge() {
+        let mut c = C::from(&[1, 2, 3][..]);
+        let snapshot = c.snapshot_left().unwrap();
+        assert!(!c.has_diverged_from(&snapshot));
+        // Read-only access doesn't count
+        assert_eq!(c.get_left(1), Some(&2));
+        assert!(!c.has_diverged_from(&snapshot.clone()));
+
+        c.push_left(4).unwrap();
+        assert!(c.has_diverged_from(&snapshot));
+        assert_eq!(&*snapshot, &[1, 2, 3]);
+    }
+
+    #[test]
+    fn clone() {
+        let mut c = C::from(&[1, 2][..]);
+        let d = c.clone();
+        let snapshot = d.snapshot_left().unwrap();
+        assert!(!c.has_diverged_from(&snapshot));
+        // Until one of them gets its own copy
+        c.push_left(3).unwrap();
+        assert!(c.has_diverged_from(&snapshot));
+        assert!(!d.has_diverged_from(&snapshot));
+    }
+
+    #[test]
+    fn replaced() {
+        let mut c = C::from(&[1][..]);
+        let snapshot = c.snapshot_left().unwrap();
+        c = C::from(&[1][..]);
+        assert!(c.has_diverged_from(&snapshot));
+        assert!(C::from(&[1][..]).has_diverged_from(&snapshot));
+        assert!(C::new_stub().snapshot_left().is_none());
+        assert!(C::new_right().snapshot_left().is_none());
+    }
+}
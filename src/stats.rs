@@ -0,0 +1,106 @@
+//! Reporting of the memory usage.
+
+use core::mem;
+
+use super::{CoWec, CoWecBlock, GrowthPolicy, LenCap, RefCnt};
+
+/// The heap usage of a [`CoWec`], as returned by [`CoWec::memory_stats`].
+///
+/// All the sizes are in bytes and include the header of the block. If the block is shared, all its
+/// owners report the same numbers (use the [`refcount`][MemoryStats::refcount] to split them
+/// fairly).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct MemoryStats {
+    /// The whole allocation, with all the capacity.
+    pub allocated_bytes: usize,
+    /// The part holding the header and the elements.
+    pub used_bytes: usize,
+    /// The unused capacity.
+    pub wasted_bytes: usize,
+    /// How many `CoWec`s share the block.
+    pub refcount: u16,
+}
+
+impl MemoryStats {
+    /// Collects the stats of a block.
+    ///
+    /// # Safety
+    ///
+    /// The block must be valid.
+    unsafe fn of_block<R: RefCnt, T, L: LenCap>(block: *const CoWecBlock<R, T, L>) -> Self {
+        let header = CoWecBlock::<R, T, L>::DATA_OFFSET;
+        // A tight block also has its capacity stored in front of the header.
+        let prefix = if CoWecBlock::is_tight(block) {
+            CoWecBlock::<R, T, L>::TIGHT_OFFSET
+        } else {
+            0
+        };
+        let allocated_bytes = prefix + header + CoWecBlock::capacity(block) * mem::size_of::<T>();
+        let used_bytes = prefix + header + (*block).len() * mem::size_of::<T>();
+        Self {
+            allocated_bytes,
+            used_bytes,
+            wasted_bytes: allocated_bytes - used_bytes,
+            refcount: (*block).rcell.count(),
+        }
+    }
+}
+
+impl<R, T, U, L, G> CoWec<R, T, U, L, G>
+where
+    R: RefCnt,
+    L: LenCap,
+    G: GrowthPolicy,
+{
+    /// Reports how much heap memory the block takes.
+    ///
+    /// The stub doesn't allocate, so it reports all zeros. Memory owned by the elements themselves
+    /// (eg. the content of a `String`) is not included.
+    pub fn memory_stats(&self) -> MemoryStats {
+        unsafe {
+            if let Some(block) = self.left_block() {
+                MemoryStats::of_block(block)
+            } else if let Some(block) = self.right_block() {
+                MemoryStats::of_block(block)
+            } else {
+                MemoryStats::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RCell;
+
+    type C = CoWec<RCell, u32, u64>;
+
+    #[test]
+    fn stats() {
+        assert_eq!(C::new_stub().memory_stats(), MemoryStats::default());
+
+        let mut c = C::from(&[1, 2, 3][..]);
+        let stats = c.memory_stats();
+        // 4 bytes of header, room for 4 elements
+        assert_eq!(stats.allocated_bytes, 4 + 4 * 4);
+        assert_eq!(stats.used_bytes, 4 + 3 * 4);
+        assert_eq!(stats.wasted_bytes, 4);
+        assert_eq!(stats.refcount, 1);
+
+        let shared = c.clone();
+        assert_eq!(shared.memory_stats().refcount, 2);
+        c.shrink_to_fit_left();
+        assert_eq!(c.memory_stats().wasted_bytes, 4);
+        drop(shared);
+        c.shrink_to_fit_left();
+        let stats = c.memory_stats();
+        assert_eq!(stats.allocated_bytes, stats.used_bytes);
+        assert_eq!(stats.wasted_bytes, 0);
+
+        // Aligned for the u64, the header takes 8 bytes
+        let stats = C::new_right().memory_stats();
+        assert_eq!(stats.allocated_bytes, 8 + 2 * 8);
+        assert_eq!(stats.used_bytes, 8);
+    }
+}